@@ -0,0 +1,359 @@
+use crate::drawer::update_task_status;
+use crate::git::{repo_root, run_git};
+use crate::shell::ShellConfig;
+use crate::terminal::{spawn_terminal, terminate_terminal, StartupOptions, TerminalExit, TerminalState};
+use crate::worktree::{create_worktree_internal, remove_worktree_internal};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use uuid::Uuid;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentStatus {
+    /// Claude is running in the agent's terminal
+    Running,
+    /// The terminal exited; the branch is waiting to be merged or discarded
+    Done,
+    Merged,
+    Discarded,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AgentInfo {
+    pub id: String,
+    pub project_path: String,
+    pub task_id: String,
+    pub task_title: String,
+    pub branch: String,
+    pub worktree_path: String,
+    pub terminal_id: String,
+    /// Commit the agent branch was created from
+    pub base_commit: String,
+    pub status: AgentStatus,
+    pub started_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AgentSummary {
+    #[serde(flatten)]
+    pub agent: AgentInfo,
+    /// Commits on the agent branch since `base_commit`
+    pub commits_ahead: usize,
+    /// Uncommitted files in the agent's worktree
+    pub changed_files: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MergeOutcome {
+    pub merged: bool,
+    /// Files left conflicted in the main worktree when the merge could not complete
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StartAgentOptions {
+    pub project_path: String,
+    pub task_id: String,
+    pub task_title: String,
+    /// Initial prompt passed to Claude on the command line
+    pub prompt: Option<String>,
+    pub ralph_mode: Option<bool>,
+    /// Commit-ish to branch from; defaults to HEAD
+    pub base: Option<String>,
+}
+
+#[derive(Default)]
+pub struct AgentState {
+    agents: Mutex<HashMap<String, AgentInfo>>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Quote a string for a POSIX shell command line
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Turn a task title into a short, branch-safe slug
+fn slugify(title: &str) -> String {
+    let slug: String = title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .take(6)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn set_status(app: &AppHandle, id: &str, status: AgentStatus) {
+    let state = app.state::<AgentState>();
+    let updated = {
+        let mut agents = state.agents.lock().unwrap();
+        agents.get_mut(id).map(|agent| {
+            agent.status = status.clone();
+            if status != AgentStatus::Running && agent.finished_at.is_none() {
+                agent.finished_at = Some(now_secs());
+            }
+            agent.clone()
+        })
+    };
+    if let Some(agent) = updated {
+        let _ = app.emit("agent-status", agent);
+    }
+}
+
+fn get_agent(state: &AgentState, id: &str) -> Result<AgentInfo, String> {
+    state
+        .agents
+        .lock()
+        .unwrap()
+        .get(id)
+        .cloned()
+        .ok_or_else(|| format!("Agent not found: {}", id))
+}
+
+fn summarize(agent: AgentInfo) -> AgentSummary {
+    let range = format!("{}..{}", agent.base_commit, agent.branch);
+    let commits_ahead = run_git(&agent.worktree_path, &["rev-list", "--count", &range])
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+    let changed_files = run_git(&agent.worktree_path, &["status", "--porcelain"])
+        .map(|s| s.lines().filter_map(|l| l.get(3..)).map(|l| l.to_string()).collect())
+        .unwrap_or_default();
+    AgentSummary {
+        agent,
+        commits_ahead,
+        changed_files,
+    }
+}
+
+/// Stop Claude and anything it started; the terminal may already be gone if the user closed it
+fn stop_terminal(terminals: &TerminalState, agent: &AgentInfo) {
+    if let Err(e) = terminate_terminal(&terminals.terminals, &agent.terminal_id) {
+        println!("[DEBUG] Failed to terminate agent terminal: {}", e);
+    }
+}
+
+/// Release an agent's terminal, worktree and branch
+fn cleanup(terminals: &TerminalState, project_path: &str, agent: &AgentInfo) -> Result<(), String> {
+    // The worktree must not be deleted under a running agent
+    stop_terminal(terminals, agent);
+    remove_worktree_internal(project_path, &agent.worktree_path, true)?;
+    run_git(project_path, &["branch", "-D", &agent.branch])?;
+    Ok(())
+}
+
+/// Create a worktree on a fresh branch for a drawer task and start Claude in it
+#[tauri::command]
+pub async fn start_agent(app: AppHandle, options: StartAgentOptions) -> Result<AgentInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || launch_agent(&app, options))
+        .await
+        .map_err(|e| format!("Failed to start agent: {}", e))?
+}
+
+fn launch_agent(app: &AppHandle, options: StartAgentOptions) -> Result<AgentInfo, String> {
+    let state = app.state::<AgentState>();
+    let terminals = app.state::<TerminalState>();
+    let StartAgentOptions {
+        project_path,
+        task_id,
+        task_title,
+        prompt,
+        ralph_mode,
+        base,
+    } = options;
+    let root = repo_root(&project_path)?;
+    let id = Uuid::new_v4().simple().to_string()[..8].to_string();
+    let slug = slugify(&task_title);
+    let branch = if slug.is_empty() {
+        format!("milhouse/agent-{}", id)
+    } else {
+        format!("milhouse/{}-{}", slug, id)
+    };
+
+    let base = base.unwrap_or_else(|| "HEAD".to_string());
+    let base_commit = run_git(&root, &["rev-parse", "--verify", &format!("{}^{{commit}}", base)])?
+        .trim()
+        .to_string();
+    let worktree = create_worktree_internal(&root, &branch, true, Some(&base_commit), None)?;
+
+    let mut command = "claude".to_string();
+    if ralph_mode.unwrap_or(false) {
+        command.push_str(" --dangerously-skip-permissions");
+    }
+    if let Some(ref p) = prompt {
+        command.push(' ');
+        command.push_str(&shell_quote(p));
+    }
+
+    let terminal_id = match spawn_terminal(
+        &terminals,
+        app,
+        Some(worktree.path.clone()),
+        Some(command),
+        &ShellConfig::default(),
+        StartupOptions::default(),
+        false,
+    ) {
+        Ok(terminal_id) => terminal_id,
+        Err(e) => {
+            let _ = remove_worktree_internal(&root, &worktree.path, true);
+            let _ = run_git(&root, &["branch", "-D", &branch]);
+            return Err(e);
+        }
+    };
+
+    let agent = AgentInfo {
+        id: id.clone(),
+        project_path: root,
+        task_id: task_id.clone(),
+        task_title,
+        branch,
+        worktree_path: worktree.path,
+        terminal_id: terminal_id.clone(),
+        base_commit,
+        status: AgentStatus::Running,
+        started_at: now_secs(),
+        finished_at: None,
+    };
+    state.agents.lock().unwrap().insert(id.clone(), agent.clone());
+
+    // Mark the agent done once its Claude terminal exits
+    let app_for_exit = app.clone();
+    let listener = std::sync::Arc::new(Mutex::new(None));
+    let listener_for_handler = listener.clone();
+    let event_id = app.listen("terminal-exit", move |event| {
+        let Ok(exited) = serde_json::from_str::<TerminalExit>(event.payload()) else {
+            return;
+        };
+        if exited.id == terminal_id {
+            let running = get_agent(&app_for_exit.state::<AgentState>(), &id)
+                .map(|a| a.status == AgentStatus::Running)
+                .unwrap_or(false);
+            if running {
+                set_status(&app_for_exit, &id, AgentStatus::Done);
+            }
+            if let Some(event_id) = listener_for_handler.lock().unwrap().take() {
+                app_for_exit.unlisten(event_id);
+            }
+        }
+    });
+    *listener.lock().unwrap() = Some(event_id);
+
+    let _ = update_task_status(task_id, "in_progress".to_string(), Some(agent.project_path.clone()));
+    let _ = app.emit("agent-status", agent.clone());
+
+    Ok(agent)
+}
+
+#[tauri::command]
+pub fn list_agents(state: State<'_, AgentState>, project_path: Option<String>) -> Result<Vec<AgentSummary>, String> {
+    let root = project_path.map(|p| repo_root(&p)).transpose()?;
+    let agents: Vec<AgentInfo> = state
+        .agents
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|a| root.is_none() || root.as_ref() == Some(&a.project_path))
+        .cloned()
+        .collect();
+
+    let mut summaries: Vec<AgentSummary> = agents
+        .into_iter()
+        .map(|agent| match agent.status {
+            AgentStatus::Running | AgentStatus::Done => summarize(agent),
+            _ => AgentSummary {
+                agent,
+                commits_ahead: 0,
+                changed_files: vec![],
+            },
+        })
+        .collect();
+    summaries.sort_by_key(|s| s.agent.started_at);
+    Ok(summaries)
+}
+
+/// Stop the agent, commit any leftover work on its branch and merge it into the main worktree.
+/// On conflicts the merge is left in progress and the conflicted files are returned.
+#[tauri::command]
+pub async fn merge_agent(app: AppHandle, id: String, commit_message: Option<String>) -> Result<MergeOutcome, String> {
+    tauri::async_runtime::spawn_blocking(move || merge_agent_branch(&app, &id, commit_message))
+        .await
+        .map_err(|e| format!("Failed to merge agent: {}", e))?
+}
+
+fn merge_agent_branch(app: &AppHandle, id: &str, commit_message: Option<String>) -> Result<MergeOutcome, String> {
+    let terminals = app.state::<TerminalState>();
+    let agent = get_agent(&app.state::<AgentState>(), id)?;
+    if matches!(agent.status, AgentStatus::Merged | AgentStatus::Discarded) {
+        return Err(format!("Agent {} is already finished", id));
+    }
+
+    // Claude must not keep writing to the worktree while its changes are committed
+    stop_terminal(&terminals, &agent);
+
+    let pending = run_git(&agent.worktree_path, &["status", "--porcelain"])?;
+    if !pending.trim().is_empty() {
+        let message = commit_message.unwrap_or_else(|| agent.task_title.clone());
+        run_git(&agent.worktree_path, &["add", "-A"])?;
+        run_git(&agent.worktree_path, &["commit", "-m", &message])?;
+    }
+
+    let merge_message = format!("Merge {} ({})", agent.branch, agent.task_title);
+    if let Err(e) = run_git(&agent.project_path, &["merge", "--no-ff", "-m", &merge_message, &agent.branch]) {
+        let conflicts: Vec<String> =
+            run_git(&agent.project_path, &["diff", "--name-only", "--diff-filter=U"])?
+                .lines()
+                .map(|l| l.to_string())
+                .collect();
+        if conflicts.is_empty() {
+            return Err(e);
+        }
+        return Ok(MergeOutcome {
+            merged: false,
+            conflicts,
+        });
+    }
+
+    cleanup(&terminals, &agent.project_path, &agent)?;
+    set_status(app, id, AgentStatus::Merged);
+    let _ = update_task_status(agent.task_id, "completed".to_string(), Some(agent.project_path));
+
+    Ok(MergeOutcome {
+        merged: true,
+        conflicts: vec![],
+    })
+}
+
+/// Stop the agent and throw away its worktree and branch
+#[tauri::command]
+pub async fn discard_agent(app: AppHandle, id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let agent = get_agent(&app.state::<AgentState>(), &id)?;
+        if matches!(agent.status, AgentStatus::Merged | AgentStatus::Discarded) {
+            return Err(format!("Agent {} is already finished", id));
+        }
+
+        cleanup(&app.state::<TerminalState>(), &agent.project_path, &agent)?;
+        set_status(&app, &id, AgentStatus::Discarded);
+        let _ = update_task_status(agent.task_id, "pending".to_string(), Some(agent.project_path));
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to discard agent: {}", e))?
+}
@@ -0,0 +1,347 @@
+use crate::git::{repo_root, run_git, run_git_with_env, MILHOUSE_DIR};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+/// Checkpoints live under their own ref namespace so they never show up as
+/// branches and are never touched by the user's own git workflow
+const CHECKPOINT_REF_PREFIX: &str = "refs/milhouse/checkpoints/";
+
+/// Identity used for checkpoint commits, so they work even without a configured git user
+const CHECKPOINT_IDENTITY: [(&str, &str); 4] = [
+    ("GIT_AUTHOR_NAME", "Milhouse"),
+    ("GIT_AUTHOR_EMAIL", "milhouse@localhost"),
+    ("GIT_COMMITTER_NAME", "Milhouse"),
+    ("GIT_COMMITTER_EMAIL", "milhouse@localhost"),
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Checkpoint {
+    pub id: String,
+    pub commit: String,
+    pub label: String,
+    pub timestamp: i64,
+    /// The commit HEAD pointed at when the checkpoint was taken
+    pub head: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct RestoreResult {
+    pub restored: Checkpoint,
+    /// Snapshot of the working tree taken just before restoring, so a restore can be undone
+    pub backup: Checkpoint,
+    pub removed_files: Vec<String>,
+}
+
+/// Active interval timers, keyed by project path
+#[derive(Default)]
+pub struct CheckpointState {
+    timers: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Run a git command against a private index file, leaving the user's index untouched
+fn git_with_index(root: &str, index: &str, args: &[&str]) -> Result<String, String> {
+    let mut envs = vec![("GIT_INDEX_FILE", index)];
+    envs.extend_from_slice(&CHECKPOINT_IDENTITY);
+    run_git_with_env(root, args, &envs)
+}
+
+/// Path for a throwaway index file inside the repository's git directory
+fn temp_index_path(root: &str) -> Result<String, String> {
+    let git_dir = run_git(root, &["rev-parse", "--absolute-git-dir"])?;
+    Ok(Path::new(git_dir.trim())
+        .join(format!("milhouse-index-{}", Uuid::new_v4().simple()))
+        .to_string_lossy()
+        .to_string())
+}
+
+fn head_commit(root: &str) -> Option<String> {
+    run_git(root, &["rev-parse", "--verify", "--quiet", "HEAD"])
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Drop Milhouse's own data from a private index. History, recordings and task lists exist to
+/// recover from a bad session, so restoring a checkpoint must never roll them back.
+fn drop_milhouse_data(root: &str, index: &str) -> Result<(), String> {
+    git_with_index(
+        root,
+        index,
+        &["rm", "-r", "-q", "--cached", "--ignore-unmatch", "--", &format!(":(glob)**/{}/**", MILHOUSE_DIR)],
+    )
+    .map(|_| ())
+}
+
+fn is_milhouse_data(path: &str) -> bool {
+    path.split('/').any(|component| component == MILHOUSE_DIR)
+}
+
+/// Write the current working tree (tracked and untracked, minus ignored files and Milhouse
+/// data) as a tree object
+fn snapshot_tree(root: &str) -> Result<String, String> {
+    let index = temp_index_path(root)?;
+
+    let result = (|| {
+        // Seeding from HEAD keeps tracked files that match .gitignore, which `add -A` alone would leave out
+        if head_commit(root).is_some() {
+            git_with_index(root, &index, &["read-tree", "HEAD"])?;
+            drop_milhouse_data(root, &index)?;
+        }
+        let exclude = format!(":(exclude,glob)**/{}/**", MILHOUSE_DIR);
+        git_with_index(root, &index, &["add", "-A", "--", ".", &exclude])?;
+        git_with_index(root, &index, &["write-tree"]).map(|s| s.trim().to_string())
+    })();
+
+    let _ = fs::remove_file(&index);
+    result
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+/// Take a checkpoint of `project_path`. When `skip_unchanged` is set and the working
+/// tree matches the most recent checkpoint, nothing is recorded and `None` is returned.
+pub fn create_checkpoint_internal(
+    project_path: &str,
+    label: &str,
+    skip_unchanged: bool,
+) -> Result<Option<Checkpoint>, String> {
+    let root = repo_root(project_path)?;
+    let tree = snapshot_tree(&root)?;
+
+    if skip_unchanged {
+        if let Some(latest) = list_checkpoints_internal(&root)?.first() {
+            let latest_tree = run_git(&root, &["rev-parse", &format!("{}^{{tree}}", latest.commit)])?;
+            if latest_tree.trim() == tree {
+                return Ok(None);
+            }
+        }
+    }
+
+    let head = head_commit(&root);
+    let mut args = vec!["commit-tree", tree.as_str(), "-m", label];
+    if let Some(ref parent) = head {
+        args.push("-p");
+        args.push(parent);
+    }
+    let commit = run_git_with_env(&root, &args, &CHECKPOINT_IDENTITY)?
+        .trim()
+        .to_string();
+
+    let id = format!("{}-{}", now_millis(), &Uuid::new_v4().simple().to_string()[..8]);
+    let ref_name = format!("{}{}", CHECKPOINT_REF_PREFIX, id);
+    run_git(&root, &["update-ref", &ref_name, &commit])?;
+
+    println!("[DEBUG] Created checkpoint {} ({}) in {}", id, label, root);
+
+    Ok(Some(Checkpoint {
+        id,
+        commit,
+        label: label.to_string(),
+        timestamp: (now_millis() / 1000) as i64,
+        head,
+    }))
+}
+
+fn list_checkpoints_internal(root: &str) -> Result<Vec<Checkpoint>, String> {
+    let output = run_git(
+        root,
+        &[
+            "for-each-ref",
+            "--sort=-refname",
+            "--format=%(refname)%00%(objectname)%00%(creatordate:unix)%00%(parent)%00%(contents:subject)",
+            CHECKPOINT_REF_PREFIX,
+        ],
+    )?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\0');
+            let id = parts.next()?.strip_prefix(CHECKPOINT_REF_PREFIX)?.to_string();
+            let commit = parts.next()?.to_string();
+            let timestamp = parts.next()?.parse().unwrap_or(0);
+            let head = parts
+                .next()
+                .and_then(|p| p.split_whitespace().next())
+                .map(|p| p.to_string());
+            let label = parts.next().unwrap_or("").to_string();
+            Some(Checkpoint {
+                id,
+                commit,
+                label,
+                timestamp,
+                head,
+            })
+        })
+        .collect())
+}
+
+fn find_checkpoint(root: &str, id: &str) -> Result<Checkpoint, String> {
+    list_checkpoints_internal(root)?
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("Checkpoint not found: {}", id))
+}
+
+/// Remove `path` and any parent directories it leaves empty, stopping at `root`
+fn remove_file_and_empty_parents(root: &Path, relative: &str) {
+    let path = root.join(relative);
+    if fs::remove_file(&path).is_err() {
+        return;
+    }
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if d == root || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+#[tauri::command]
+pub async fn create_checkpoint(project_path: String, label: Option<String>) -> Result<Checkpoint, String> {
+    let label = label.unwrap_or_else(|| "Manual checkpoint".to_string());
+    // Snapshotting hashes the whole worktree, which can take a while on large repositories
+    tauri::async_runtime::spawn_blocking(move || {
+        create_checkpoint_internal(&project_path, &label, false)?
+            .ok_or_else(|| "Checkpoint was not created".to_string())
+    })
+    .await
+    .map_err(|e| format!("Checkpoint task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn list_checkpoints(project_path: String) -> Result<Vec<Checkpoint>, String> {
+    let root = repo_root(&project_path)?;
+    list_checkpoints_internal(&root)
+}
+
+#[tauri::command]
+pub async fn restore_checkpoint(project_path: String, id: String) -> Result<RestoreResult, String> {
+    tauri::async_runtime::spawn_blocking(move || restore_checkpoint_internal(&project_path, &id))
+        .await
+        .map_err(|e| format!("Checkpoint task failed: {}", e))?
+}
+
+fn restore_checkpoint_internal(project_path: &str, id: &str) -> Result<RestoreResult, String> {
+    let root = repo_root(project_path)?;
+    let target = find_checkpoint(&root, id)?;
+
+    // Snapshot the current state first so the restore itself can be rolled back
+    let backup = create_checkpoint_internal(&root, &format!("Before restoring {}", id), false)?
+        .ok_or_else(|| "Failed to back up working tree".to_string())?;
+
+    let index = temp_index_path(&root)?;
+    let result = (|| {
+        git_with_index(&root, &index, &["read-tree", &target.commit])?;
+        // Checkpoints taken by older versions may still contain Milhouse data
+        drop_milhouse_data(&root, &index)?;
+        git_with_index(&root, &index, &["checkout-index", "-a", "-f"])
+    })();
+    let _ = fs::remove_file(&index);
+    result?;
+
+    // Files created after the checkpoint are not in its tree, so remove them explicitly
+    let added = run_git(
+        &root,
+        &["diff-tree", "-r", "-z", "--name-only", "--diff-filter=A", &target.commit, &backup.commit],
+    )?;
+    let removed_files: Vec<String> = added
+        .split('\0')
+        .filter(|p| !p.is_empty() && !is_milhouse_data(p))
+        .map(|p| p.to_string())
+        .collect();
+    for file in &removed_files {
+        remove_file_and_empty_parents(Path::new(&root), file);
+    }
+
+    println!(
+        "[DEBUG] Restored checkpoint {} in {} ({} files removed)",
+        id,
+        root,
+        removed_files.len()
+    );
+
+    Ok(RestoreResult {
+        restored: target,
+        backup,
+        removed_files,
+    })
+}
+
+#[tauri::command]
+pub fn delete_checkpoint(project_path: String, id: String) -> Result<(), String> {
+    let root = repo_root(&project_path)?;
+    find_checkpoint(&root, &id)?;
+    run_git(&root, &["update-ref", "-d", &format!("{}{}", CHECKPOINT_REF_PREFIX, id)])?;
+    Ok(())
+}
+
+/// Take a checkpoint every `interval_secs` while the working tree keeps changing
+#[tauri::command]
+pub fn start_checkpoint_timer(
+    app: AppHandle,
+    state: State<'_, CheckpointState>,
+    project_path: String,
+    interval_secs: u64,
+) -> Result<(), String> {
+    if interval_secs == 0 {
+        return Err("Checkpoint interval must be greater than zero".to_string());
+    }
+    let root = repo_root(&project_path)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = timers.insert(root.clone(), stop.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+
+    thread::spawn(move || {
+        let interval = Duration::from_secs(interval_secs);
+        loop {
+            // Sleep in short steps so stopping the timer takes effect promptly
+            let started = std::time::Instant::now();
+            while started.elapsed() < interval {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(500));
+            }
+
+            match create_checkpoint_internal(&root, "Automatic checkpoint", true) {
+                Ok(Some(checkpoint)) => {
+                    let _ = app.emit("checkpoint-created", checkpoint);
+                }
+                Ok(None) => {}
+                Err(e) => println!("[DEBUG] Automatic checkpoint failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_checkpoint_timer(state: State<'_, CheckpointState>, project_path: String) -> Result<(), String> {
+    let root = repo_root(&project_path)?;
+    let mut timers = state.timers.lock().map_err(|e| e.to_string())?;
+    if let Some(stop) = timers.remove(&root) {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
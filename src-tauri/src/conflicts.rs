@@ -0,0 +1,421 @@
+use crate::git::{repo_root, run_git};
+use crate::local_history::record_version;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConflictedFile {
+    pub path: String,
+    /// Two-letter porcelain status, e.g. "UU" (both modified) or "AA" (both added)
+    pub status: String,
+    /// Number of conflict regions in the file; zero when the file is deleted on one side
+    pub conflict_count: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConflictStatus {
+    /// Operation in progress: "merge", "rebase", "cherry-pick" or "revert"
+    pub operation: Option<String>,
+    pub files: Vec<ConflictedFile>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConflictSegment {
+    /// Text outside any conflict
+    Common { text: String },
+    Conflict {
+        index: usize,
+        /// 1-based line of the `<<<<<<<` marker
+        start_line: usize,
+        /// 1-based line of the `>>>>>>>` marker
+        end_line: usize,
+        ours_label: String,
+        theirs_label: String,
+        ours: String,
+        /// Common ancestor text, present when the file was written with diff3 style markers
+        #[serde(skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+        theirs: String,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    pub index: usize,
+    /// "ours", "theirs", "base", "ours_then_theirs", "theirs_then_ours" or "custom"
+    pub choice: String,
+    /// Replacement text when `choice` is "custom"
+    pub text: Option<String>,
+}
+
+/// Whether `line` starts with `marker` followed by end of line or a space
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .map(|rest| rest.is_empty() || rest.starts_with(' ') || rest.starts_with('\r') || rest.starts_with('\n'))
+        .unwrap_or(false)
+}
+
+fn marker_label(line: &str) -> String {
+    line[7..].trim().to_string()
+}
+
+/// Split file content into common text and conflict regions
+pub fn parse_conflict_markers(content: &str) -> Result<Vec<ConflictSegment>, String> {
+    enum Section {
+        Common,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut segments = Vec::new();
+    let mut section = Section::Common;
+    let mut common = String::new();
+    let (mut ours, mut base, mut theirs) = (String::new(), None::<String>, String::new());
+    let (mut ours_label, mut start_line) = (String::new(), 0);
+
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let line_number = i + 1;
+        match section {
+            Section::Common if is_marker(line, OURS_MARKER) => {
+                if !common.is_empty() {
+                    segments.push(ConflictSegment::Common {
+                        text: std::mem::take(&mut common),
+                    });
+                }
+                ours_label = marker_label(line);
+                start_line = line_number;
+                section = Section::Ours;
+            }
+            Section::Common => common.push_str(line),
+            Section::Ours if is_marker(line, BASE_MARKER) => {
+                base = Some(String::new());
+                section = Section::Base;
+            }
+            Section::Ours | Section::Base if is_marker(line, SEPARATOR_MARKER) => section = Section::Theirs,
+            Section::Ours => ours.push_str(line),
+            Section::Base => base.get_or_insert_with(String::new).push_str(line),
+            Section::Theirs if is_marker(line, THEIRS_MARKER) => {
+                segments.push(ConflictSegment::Conflict {
+                    index: segments
+                        .iter()
+                        .filter(|s| matches!(s, ConflictSegment::Conflict { .. }))
+                        .count(),
+                    start_line,
+                    end_line: line_number,
+                    ours_label: std::mem::take(&mut ours_label),
+                    theirs_label: marker_label(line),
+                    ours: std::mem::take(&mut ours),
+                    base: base.take(),
+                    theirs: std::mem::take(&mut theirs),
+                });
+                section = Section::Common;
+            }
+            Section::Theirs => theirs.push_str(line),
+        }
+    }
+
+    if !matches!(section, Section::Common) {
+        return Err(format!("Unterminated conflict starting at line {}", start_line));
+    }
+    if !common.is_empty() {
+        segments.push(ConflictSegment::Common { text: common });
+    }
+    Ok(segments)
+}
+
+fn count_conflicts(segments: &[ConflictSegment]) -> usize {
+    segments
+        .iter()
+        .filter(|s| matches!(s, ConflictSegment::Conflict { .. }))
+        .count()
+}
+
+/// Whether `content` still has conflict regions, including unterminated ones
+fn has_conflict_markers(content: &str) -> bool {
+    parse_conflict_markers(content)
+        .map(|segments| count_conflicts(&segments) > 0)
+        .unwrap_or(true)
+}
+
+/// Work out which multi-step operation is in progress from the marker files git leaves behind
+fn current_operation(root: &str) -> Option<String> {
+    let exists = |name: &str| {
+        run_git(root, &["rev-parse", "--git-path", name])
+            .map(|p| Path::new(root).join(p.trim()).exists())
+            .unwrap_or(false)
+    };
+    if exists("rebase-merge") || exists("rebase-apply") {
+        Some("rebase".to_string())
+    } else if exists("MERGE_HEAD") {
+        Some("merge".to_string())
+    } else if exists("CHERRY_PICK_HEAD") {
+        Some("cherry-pick".to_string())
+    } else if exists("REVERT_HEAD") {
+        Some("revert".to_string())
+    } else {
+        None
+    }
+}
+
+#[tauri::command]
+pub fn list_conflicts(project_path: String) -> Result<ConflictStatus, String> {
+    let root = repo_root(&project_path)?;
+    let status = run_git(&root, &["status", "--porcelain", "-z"])?;
+
+    let mut files = Vec::new();
+    for entry in status.split('\0') {
+        if entry.len() < 4 {
+            continue;
+        }
+        let code = &entry[..2];
+        if !matches!(code, "UU" | "AA" | "DD" | "AU" | "UA" | "DU" | "UD") {
+            continue;
+        }
+        let path = entry[3..].to_string();
+        let conflict_count = fs::read_to_string(Path::new(&root).join(&path))
+            .ok()
+            .and_then(|content| parse_conflict_markers(&content).ok())
+            .map(|segments| count_conflicts(&segments))
+            .unwrap_or(0);
+        files.push(ConflictedFile {
+            path,
+            status: code.to_string(),
+            conflict_count,
+        });
+    }
+
+    Ok(ConflictStatus {
+        operation: current_operation(&root),
+        files,
+    })
+}
+
+/// Parse a conflicted file into common text and ours/base/theirs regions
+#[tauri::command]
+pub fn parse_conflicts(project_path: String, path: String) -> Result<Vec<ConflictSegment>, String> {
+    let root = repo_root(&project_path)?;
+    let content = fs::read_to_string(Path::new(&root).join(&path))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    parse_conflict_markers(&content)
+}
+
+/// Apply a choice to every conflict region, write the result and mark the file resolved.
+/// When `content` is given it is written as-is instead (a hand-edited resolution).
+#[tauri::command]
+pub fn resolve_conflicts(
+    project_path: String,
+    path: String,
+    resolutions: Option<Vec<ConflictResolution>>,
+    content: Option<String>,
+) -> Result<(), String> {
+    let root = repo_root(&project_path)?;
+    let file_path = Path::new(&root).join(&path);
+
+    let resolved = match content {
+        Some(content) => content,
+        None => {
+            let resolutions = resolutions.unwrap_or_default();
+            let current =
+                fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+            let mut output = String::new();
+            for segment in parse_conflict_markers(&current)? {
+                match segment {
+                    ConflictSegment::Common { text } => output.push_str(&text),
+                    ConflictSegment::Conflict {
+                        index,
+                        ours,
+                        base,
+                        theirs,
+                        ..
+                    } => {
+                        let resolution = resolutions
+                            .iter()
+                            .find(|r| r.index == index)
+                            .ok_or_else(|| format!("No resolution given for conflict {}", index))?;
+                        match resolution.choice.as_str() {
+                            "ours" => output.push_str(&ours),
+                            "theirs" => output.push_str(&theirs),
+                            "base" => output.push_str(
+                                &base.ok_or_else(|| format!("Conflict {} has no base version", index))?,
+                            ),
+                            "ours_then_theirs" => {
+                                output.push_str(&ours);
+                                output.push_str(&theirs);
+                            }
+                            "theirs_then_ours" => {
+                                output.push_str(&theirs);
+                                output.push_str(&ours);
+                            }
+                            "custom" => output.push_str(resolution.text.as_deref().unwrap_or("")),
+                            other => return Err(format!("Unknown resolution choice: {}", other)),
+                        }
+                    }
+                }
+            }
+            output
+        }
+    };
+
+    if has_conflict_markers(&resolved) {
+        return Err("Resolved content still contains conflict markers".to_string());
+    }
+
+    // Keep the conflicted version around in case the resolution needs to be redone
+    let _ = record_version(&root, Path::new(&path), "before-resolve");
+    fs::write(&file_path, resolved).map_err(|e| format!("Failed to write file: {}", e))?;
+    stage_resolution(&root, &path)
+}
+
+/// Record `path` as resolved in the index. A file that is gone was resolved by deleting it
+/// (e.g. a modify/delete conflict), which `git add` cannot stage.
+fn stage_resolution(root: &str, path: &str) -> Result<(), String> {
+    if Path::new(root).join(path).exists() {
+        run_git(root, &["add", "--", path])?;
+    } else {
+        run_git(root, &["rm", "--cached", "--quiet", "--", path])?;
+    }
+    Ok(())
+}
+
+/// Mark a file resolved after it was fixed up by hand in the editor
+#[tauri::command]
+pub fn mark_resolved(project_path: String, path: String) -> Result<(), String> {
+    let root = repo_root(&project_path)?;
+    let file_path = Path::new(&root).join(&path);
+    if file_path.exists() {
+        let content = fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+        if has_conflict_markers(&content) {
+            return Err(format!("{} still contains conflict markers", path));
+        }
+    }
+    stage_resolution(&root, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The one conflict region in `content`, as (ours_label, ours, base, theirs, theirs_label)
+    fn only_conflict(content: &str) -> (String, String, Option<String>, String, String) {
+        let conflicts: Vec<ConflictSegment> = parse_conflict_markers(content)
+            .unwrap()
+            .into_iter()
+            .filter(|s| matches!(s, ConflictSegment::Conflict { .. }))
+            .collect();
+        assert_eq!(conflicts.len(), 1);
+        match conflicts.into_iter().next() {
+            Some(ConflictSegment::Conflict {
+                ours_label,
+                ours,
+                base,
+                theirs,
+                theirs_label,
+                ..
+            }) => (ours_label, ours, base, theirs, theirs_label),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parses_two_way_conflict_between_common_text() {
+        let content = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\nb\n";
+        let segments = parse_conflict_markers(content).unwrap();
+        assert_eq!(segments.len(), 3);
+        assert!(matches!(&segments[0], ConflictSegment::Common { text } if text == "a\n"));
+        assert!(matches!(
+            &segments[1],
+            ConflictSegment::Conflict { index: 0, start_line: 2, end_line: 6, .. }
+        ));
+        assert!(matches!(&segments[2], ConflictSegment::Common { text } if text == "b\n"));
+        assert_eq!(
+            only_conflict(content),
+            (
+                "HEAD".to_string(),
+                "ours\n".to_string(),
+                None,
+                "theirs\n".to_string(),
+                "feature".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn parses_diff3_base_section() {
+        let content = "<<<<<<< ours\nx = 1\n||||||| base\nx = 0\n=======\nx = 2\n>>>>>>> theirs\n";
+        let (_, ours, base, theirs, _) = only_conflict(content);
+        assert_eq!((ours.as_str(), base.as_deref(), theirs.as_str()), ("x = 1\n", Some("x = 0\n"), "x = 2\n"));
+    }
+
+    #[test]
+    fn empty_base_is_kept_apart_from_missing_base() {
+        let content = "<<<<<<< ours\nadded\n||||||| base\n=======\n>>>>>>> theirs\n";
+        let (_, _, base, theirs, _) = only_conflict(content);
+        assert_eq!(base.as_deref(), Some(""));
+        assert_eq!(theirs, "");
+    }
+
+    #[test]
+    fn numbers_conflicts_in_order() {
+        let content = "<<<<<<< a\n1\n=======\n2\n>>>>>>> b\nmid\n<<<<<<< a\n3\n=======\n4\n>>>>>>> b\n";
+        let indexes: Vec<usize> = parse_conflict_markers(content)
+            .unwrap()
+            .into_iter()
+            .filter_map(|segment| match segment {
+                ConflictSegment::Conflict { index, .. } => Some(index),
+                ConflictSegment::Common { .. } => None,
+            })
+            .collect();
+        assert_eq!(indexes, vec![0, 1]);
+    }
+
+    #[test]
+    fn handles_crlf_and_missing_final_newline() {
+        let content = "<<<<<<< HEAD\r\nours\r\n=======\r\ntheirs\r\n>>>>>>> other";
+        let (ours_label, ours, _, theirs, theirs_label) = only_conflict(content);
+        assert_eq!(ours_label, "HEAD");
+        assert_eq!(ours, "ours\r\n");
+        assert_eq!(theirs, "theirs\r\n");
+        assert_eq!(theirs_label, "other");
+    }
+
+    #[test]
+    fn ignores_lookalike_markers() {
+        // Longer runs and markers not followed by a space or line end are ordinary text
+        let content = "<<<<<<<<\n=======\n<<<<<<<x\n>>>>>>> \n";
+        let segments = parse_conflict_markers(content).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert!(matches!(&segments[0], ConflictSegment::Common { text } if text == content));
+        assert!(!has_conflict_markers(content));
+    }
+
+    #[test]
+    fn separator_lines_inside_theirs_are_content() {
+        let content = "<<<<<<< a\n1\n=======\n=======\n>>>>>>> b\n";
+        assert_eq!(only_conflict(content).3, "=======\n");
+    }
+
+    #[test]
+    fn reports_unterminated_conflict() {
+        let content = "ok\n<<<<<<< HEAD\nours\n=======\ntheirs\n";
+        assert_eq!(
+            parse_conflict_markers(content).unwrap_err(),
+            "Unterminated conflict starting at line 2"
+        );
+        assert!(has_conflict_markers(content));
+    }
+
+    #[test]
+    fn empty_content_has_no_segments() {
+        assert!(parse_conflict_markers("").unwrap().is_empty());
+        assert!(!has_conflict_markers("plain\n"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::todos::{imported_tasks, update_imported_status};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,6 +12,11 @@ pub struct TaskEntry {
     pub timestamp: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project_path: Option<String>,
+    /// File and line the task was created from, for tasks imported from TODO comments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_line: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,7 +39,8 @@ pub fn list_tasks(project_path: Option<String>) -> Result<Vec<TaskEntry>, String
     // For now, return empty array - the MCP server handles the actual data storage
     // and Claude can use the list_tasks MCP tool to query tasks
     println!("[DEBUG] list_tasks called with project_path: {:?}", project_path);
-    Ok(vec![])
+    // Tasks imported from TODO comments live in the project's .milhouse directory
+    Ok(project_path.as_deref().map(imported_tasks).unwrap_or_default())
 }
 
 /// List documents from the context store
@@ -52,10 +59,13 @@ pub fn list_documents(project_path: Option<String>) -> Result<Vec<DocumentEntry>
 /// Note: This is a stub implementation. In production, this would update
 /// the LanceDB database at ~/.milhouse/context.lance
 #[tauri::command]
-pub fn update_task_status(task_id: String, status: String) -> Result<(), String> {
+pub fn update_task_status(task_id: String, status: String, project_path: Option<String>) -> Result<(), String> {
     // TODO: Implement actual LanceDB update
     // For now, just log the request - the MCP server handles the actual data
     // and Claude can use the update_task_status MCP tool to update tasks
     println!("[DEBUG] update_task_status called: task_id={}, status={}", task_id, status);
+    if let Some(ref project_path) = project_path {
+        update_imported_status(project_path, &task_id, &status)?;
+    }
     Ok(())
 }
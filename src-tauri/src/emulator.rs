@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+/// Matches the size terminals are opened with, until the first resize
+const DEFAULT_ROWS: u16 = 24;
+const DEFAULT_COLS: u16 = 80;
+
+/// Headless VT state for a terminal: the screen grid, cursor and title, kept in step with
+/// everything the PTY writes so the backend can tell what is on screen without the webview
+pub struct TerminalScreen {
+    parser: vt100::Parser<TitleTracker>,
+}
+
+/// Collects the window title, which vt100 hands to callbacks instead of keeping on the screen
+#[derive(Default)]
+struct TitleTracker {
+    title: String,
+}
+
+impl vt100::Callbacks for TitleTracker {
+    fn set_window_title(&mut self, _: &mut vt100::Screen, title: &[u8]) {
+        self.title = String::from_utf8_lossy(title).into_owned();
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScreenSnapshot {
+    pub rows: u16,
+    pub cols: u16,
+    pub cursor_row: u16,
+    pub cursor_col: u16,
+    pub cursor_visible: bool,
+    pub title: String,
+    /// Full-screen programs such as vim or less draw on the alternate screen
+    pub alternate_screen: bool,
+    /// Visible rows, trailing blanks trimmed
+    pub text: String,
+    /// Every cell by row, when styled output was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cells: Option<Vec<Vec<ScreenCell>>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScreenCell {
+    /// Empty for blank cells and the second half of wide characters
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg: Option<CellColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<CellColor>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub bold: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub dim: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub italic: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub underline: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub inverse: bool,
+}
+
+/// A palette index or an RGB color; the default color is left out
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CellColor {
+    Index(u8),
+    Rgb([u8; 3]),
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn cell_color(color: vt100::Color) -> Option<CellColor> {
+    match color {
+        vt100::Color::Default => None,
+        vt100::Color::Idx(index) => Some(CellColor::Index(index)),
+        vt100::Color::Rgb(r, g, b) => Some(CellColor::Rgb([r, g, b])),
+    }
+}
+
+impl Default for TerminalScreen {
+    fn default() -> Self {
+        Self {
+            parser: vt100::Parser::new_with_callbacks(DEFAULT_ROWS, DEFAULT_COLS, 0, TitleTracker::default()),
+        }
+    }
+}
+
+impl TerminalScreen {
+    pub fn process(&mut self, data: &str) {
+        self.parser.process(data.as_bytes());
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        self.parser.screen_mut().set_size(rows, cols);
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.parser.screen().size()
+    }
+
+    /// Output that redraws the current screen, cursor and modes on a blank terminal
+    pub fn replay(&self) -> String {
+        String::from_utf8_lossy(&self.parser.screen().state_formatted()).into_owned()
+    }
+
+    pub fn title(&self) -> &str {
+        &self.parser.callbacks().title
+    }
+
+    pub fn snapshot(&self, styled: bool) -> ScreenSnapshot {
+        let screen = self.parser.screen();
+        let (rows, cols) = screen.size();
+        let (cursor_row, cursor_col) = screen.cursor_position();
+        let lines: Vec<String> = screen.rows(0, cols).map(|line| line.trim_end().to_string()).collect();
+        let used = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+
+        ScreenSnapshot {
+            rows,
+            cols,
+            cursor_row,
+            cursor_col,
+            cursor_visible: !screen.hide_cursor(),
+            title: self.title().to_string(),
+            alternate_screen: screen.alternate_screen(),
+            text: lines[..used].join("\n"),
+            cells: styled.then(|| {
+                (0..rows)
+                    .map(|row| (0..cols).map(|col| styled_cell(screen, row, col)).collect())
+                    .collect()
+            }),
+        }
+    }
+}
+
+fn styled_cell(screen: &vt100::Screen, row: u16, col: u16) -> ScreenCell {
+    let Some(cell) = screen.cell(row, col) else {
+        return ScreenCell::default();
+    };
+    ScreenCell {
+        text: cell.contents().to_string(),
+        fg: cell_color(cell.fgcolor()),
+        bg: cell_color(cell.bgcolor()),
+        bold: cell.bold(),
+        dim: cell.dim(),
+        italic: cell.italic(),
+        underline: cell.underline(),
+        inverse: cell.inverse(),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::local_history::record_version;
 use serde::Serialize;
 use std::fs;
 use std::path::Path;
@@ -71,6 +72,33 @@ pub fn read_file(path: String) -> Result<String, String> {
         .map_err(|e| format!("Failed to read file: {}", e))
 }
 
+/// Write a file, recording the previous and new content in the project's local history
+#[tauri::command]
+pub fn write_file(path: String, content: String, project_path: Option<String>) -> Result<(), String> {
+    let file_path = Path::new(&path);
+
+    if file_path.is_dir() {
+        return Err(format!("Path is a directory: {}", path));
+    }
+
+    if let Some(ref root) = project_path {
+        if let Err(e) = record_version(root, file_path, "before-write") {
+            println!("[DEBUG] Failed to record history for {}: {}", path, e);
+        }
+    }
+
+    fs::write(file_path, content)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    if let Some(ref root) = project_path {
+        if let Err(e) = record_version(root, file_path, "write") {
+            println!("[DEBUG] Failed to record history for {}: {}", path, e);
+        }
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_home_dir() -> Result<String, String> {
     dirs::home_dir()
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Per-project formatter overrides, keyed by file extension
+const PROJECT_CONFIG_PATH: &str = ".milhouse/formatters.json";
+
+/// Formatters taking longer than this are killed
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Placeholder in formatter args replaced with the file's path
+const FILE_PLACEHOLDER: &str = "{file}";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FormatOutcome {
+    Formatted {
+        text: String,
+        /// Whether the formatter changed anything
+        changed: bool,
+        formatter: String,
+    },
+    Error {
+        /// "not_configured", "not_found", "timeout" or "failed"
+        kind: String,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stderr: Option<String>,
+    },
+}
+
+fn formatter(command: &str, args: &[&str]) -> FormatterConfig {
+    FormatterConfig {
+        command: command.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        timeout_ms: None,
+    }
+}
+
+fn default_config(extension: &str) -> Option<FormatterConfig> {
+    match extension {
+        "rs" => Some(formatter("rustfmt", &["--edition", "2021", "--emit", "stdout"])),
+        "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" | "json" | "css" | "scss" | "less" | "html"
+        | "vue" | "md" | "yaml" | "yml" => Some(formatter(
+            "npx",
+            &["--no-install", "prettier", "--stdin-filepath", FILE_PLACEHOLDER],
+        )),
+        "py" | "pyi" => Some(formatter("black", &["--quiet", "--stdin-filename", FILE_PLACEHOLDER, "-"])),
+        "go" => Some(formatter("gofmt", &[])),
+        _ => None,
+    }
+}
+
+/// Formatters configured in `.milhouse/formatters.json`; a `null` entry disables formatting
+fn project_overrides(root: &str) -> Result<HashMap<String, Option<FormatterConfig>>, String> {
+    let config_path = Path::new(root).join(PROJECT_CONFIG_PATH);
+    if !config_path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read formatter config: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse formatter config: {}", e))
+}
+
+/// Formatter for `path`, preferring the project's overrides
+fn resolve_config(root: Option<&str>, path: &str) -> Result<Option<FormatterConfig>, String> {
+    let Some(extension) = Path::new(path).extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()) else {
+        return Ok(None);
+    };
+    if let Some(root) = root {
+        if let Some(config) = project_overrides(root)?.remove(&extension) {
+            return Ok(config);
+        }
+    }
+    Ok(default_config(&extension))
+}
+
+fn error(kind: &str, message: String, stderr: Option<String>) -> FormatOutcome {
+    FormatOutcome::Error {
+        kind: kind.to_string(),
+        message,
+        stderr,
+    }
+}
+
+/// Pipe `content` through the formatter, killing it after the timeout
+fn run_formatter(config: &FormatterConfig, cwd: &Path, path: &str, content: &str, timeout: Duration) -> FormatOutcome {
+    let args: Vec<String> = config.args.iter().map(|a| a.replace(FILE_PLACEHOLDER, path)).collect();
+    let mut child = match Command::new(&config.command)
+        .args(&args)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return error("not_found", format!("Formatter not found: {}", config.command), None)
+        }
+        Err(e) => return error("failed", format!("Failed to start {}: {}", config.command, e), None),
+    };
+
+    // Feed stdin and drain the pipes on threads so a large buffer cannot deadlock
+    let mut stdin = child.stdin.take().unwrap();
+    let input = content.to_string();
+    let writer = thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let mut stdout = child.stdout.take().unwrap();
+    let stdout_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return error(
+                    "timeout",
+                    format!("{} timed out after {}ms", config.command, timeout.as_millis()),
+                    None,
+                );
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return error("failed", format!("Failed to wait for {}: {}", config.command, e), None),
+        }
+    };
+
+    let _ = writer.join();
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = String::from_utf8_lossy(&stderr_reader.join().unwrap_or_default()).trim().to_string();
+
+    if !status.success() {
+        return error(
+            "failed",
+            format!("{} exited with {}", config.command, status),
+            if stderr.is_empty() { None } else { Some(stderr) },
+        );
+    }
+
+    match String::from_utf8(stdout) {
+        Ok(text) => FormatOutcome::Formatted {
+            changed: text != content,
+            text,
+            formatter: config.command.clone(),
+        },
+        Err(_) => error("failed", format!("{} produced invalid UTF-8", config.command), None),
+    }
+}
+
+/// Formatter that would be used for `path`, if any
+#[tauri::command]
+pub fn get_formatter(project_path: Option<String>, path: String) -> Result<Option<FormatterConfig>, String> {
+    resolve_config(project_path.as_deref(), &path)
+}
+
+/// Format an editor buffer for `path` without touching the file on disk
+#[tauri::command]
+pub async fn format_buffer(
+    project_path: Option<String>,
+    path: String,
+    content: String,
+    timeout_ms: Option<u64>,
+) -> Result<FormatOutcome, String> {
+    let Some(config) = resolve_config(project_path.as_deref(), &path)? else {
+        return Ok(error("not_configured", format!("No formatter configured for: {}", path), None));
+    };
+    let timeout = Duration::from_millis(timeout_ms.or(config.timeout_ms).unwrap_or(DEFAULT_TIMEOUT_MS));
+
+    // Run from the file's directory so formatters find their config files (rustfmt.toml, .prettierrc)
+    let cwd = Path::new(&path)
+        .parent()
+        .filter(|p| p.is_dir())
+        .map(|p| p.to_path_buf())
+        .or_else(|| project_path.as_ref().map(|p| Path::new(p).to_path_buf()))
+        .unwrap_or_else(std::env::temp_dir);
+
+    let started = Instant::now();
+    let outcome = tauri::async_runtime::spawn_blocking(move || run_formatter(&config, &cwd, &path, &content, timeout))
+        .await
+        .map_err(|e| format!("Formatter task failed: {}", e))?;
+    println!("[DEBUG] Formatted buffer in {}ms", started.elapsed().as_millis());
+    Ok(outcome)
+}
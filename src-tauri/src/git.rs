@@ -0,0 +1,438 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Run a git command in `repo` and return its stdout
+pub fn run_git(repo: &str, args: &[&str]) -> Result<String, String> {
+    run_git_with_env(repo, args, &[])
+}
+
+/// Run a git command with extra environment variables (e.g. a private GIT_INDEX_FILE)
+pub fn run_git_with_env(repo: &str, args: &[&str], envs: &[(&str, &str)]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .envs(envs.iter().copied())
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Get the top-level directory of the repository containing `path`
+pub fn repo_root(path: &str) -> Result<String, String> {
+    run_git(path, &["rev-parse", "--show-toplevel"]).map(|s| s.trim().to_string())
+}
+
+/// Check whether `path` is inside a git work tree
+pub fn is_git_repo(path: &str) -> bool {
+    run_git(path, &["rev-parse", "--is-inside-work-tree"])
+        .map(|s| s.trim() == "true")
+        .unwrap_or(false)
+}
+
+/// Per-project directory for Milhouse's own data (history, recordings, task lists, ...)
+pub const MILHOUSE_DIR: &str = ".milhouse";
+
+/// Projects whose `.git/info/exclude` has already been checked this run
+static EXCLUDED_PROJECTS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Create `dir` inside a project's `.milhouse` directory, making sure git ignores `.milhouse/`
+/// so its data never shows up in `git status` or gets committed by `git add -A`
+pub fn create_milhouse_dir(project_path: &str, dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create .milhouse directory: {}", e))?;
+    let first_time = EXCLUDED_PROJECTS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashSet::new)
+        .insert(project_path.to_string());
+    if first_time {
+        if let Err(e) = exclude_milhouse_dir(project_path) {
+            println!("[DEBUG] Failed to exclude .milhouse from git: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Add `.milhouse/` to the repository's `info/exclude` unless it is already listed there
+fn exclude_milhouse_dir(project_path: &str) -> Result<(), String> {
+    if !is_git_repo(project_path) {
+        return Ok(());
+    }
+    // `--git-path` resolves to the common git dir, so linked worktrees share one exclude file
+    let relative = run_git(project_path, &["rev-parse", "--git-path", "info/exclude"])?;
+    let path = Path::new(project_path).join(relative.trim());
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let pattern = format!("{}/", MILHOUSE_DIR);
+    let listed = existing
+        .lines()
+        .map(|l| l.trim().trim_start_matches('/'))
+        .any(|l| l == pattern || l == MILHOUSE_DIR);
+    if listed {
+        return Ok(());
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create git info directory: {}", e))?;
+    }
+    let separator = if existing.is_empty() || existing.ends_with('\n') { "" } else { "\n" };
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}# Milhouse project data\n{}", separator, pattern)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    println!("[DEBUG] Added {} to {}", pattern, path.display());
+    Ok(())
+}
+
+/// Whether `path` under `root` is excluded by the same ignore files the project walkers honour:
+/// `.ignore` and `.gitignore` in `root` and the directories below it, `.git/info/exclude` and
+/// the global excludes file. Deeper files take precedence, and a `!pattern` re-includes.
+pub fn is_gitignored(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let is_dir = path.is_dir();
+    let dirs = relative.parent().into_iter().flat_map(|p| p.ancestors());
+    for dir in dirs.map(|d| root.join(d)) {
+        for name in [".ignore", ".gitignore"] {
+            let file = dir.join(name);
+            if !file.is_file() {
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new(&dir);
+            builder.add(&file);
+            if let Ok(matcher) = builder.build() {
+                let matched = matcher.matched_path_or_any_parents(path, is_dir);
+                if !matched.is_none() {
+                    return matched.is_ignore();
+                }
+            }
+        }
+    }
+
+    let mut builder = GitignoreBuilder::new(root);
+    builder.add(root.join(".git/info/exclude"));
+    let exclude = builder.build().ok();
+    let (global, _) = Gitignore::global();
+    exclude
+        .into_iter()
+        .chain([global])
+        .find_map(|matcher| {
+            let matched = matcher.matched_path_or_any_parents(path, is_dir);
+            (!matched.is_none()).then(|| matched.is_ignore())
+        })
+        .unwrap_or(false)
+}
+
+/// Field and record separators for machine-readable `git log` output
+const LOG_FORMAT: &str = "--format=%H%x00%h%x00%an%x00%ae%x00%at%x00%P%x00%s%x00%b%x1e";
+
+/// Diffs larger than this are truncated before being sent to the frontend
+const MAX_DIFF_BYTES: usize = 1024 * 1024;
+
+/// Hash git uses in blame output for lines that are not committed yet
+const UNCOMMITTED_HASH: &str = "0000000000000000000000000000000000000000";
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub short_hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: i64,
+    pub parents: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    /// Whether the commit was written during a Claude session (by author or co-author trailer)
+    pub claude_authored: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BlameLine {
+    pub line: usize,
+    pub original_line: usize,
+    pub commit: String,
+    pub content: String,
+}
+
+#[derive(Serialize)]
+pub struct BlameResult {
+    pub lines: Vec<BlameLine>,
+    /// Metadata for every commit referenced by `lines`, keyed by hash
+    pub commits: HashMap<String, CommitInfo>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ChangedFile {
+    pub path: String,
+    /// Single-letter git status: A, M, D, T, ...
+    pub status: String,
+    pub additions: Option<u32>,
+    pub deletions: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct CommitDetail {
+    pub commit: CommitInfo,
+    pub files: Vec<ChangedFile>,
+    pub diff: String,
+    pub diff_truncated: bool,
+}
+
+fn is_claude_commit(author_name: &str, body: &str) -> bool {
+    let body = body.to_lowercase();
+    author_name.to_lowercase().contains("claude")
+        || body.contains("co-authored-by: claude")
+        || body.contains("generated with [claude code]")
+}
+
+/// Parse output produced with `LOG_FORMAT`
+fn parse_log(output: &str) -> Vec<CommitInfo> {
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let record = record.trim_start_matches('\n');
+            if record.is_empty() {
+                return None;
+            }
+            let mut fields = record.split('\0');
+            let hash = fields.next()?.to_string();
+            let short_hash = fields.next()?.to_string();
+            let author_name = fields.next()?.to_string();
+            let author_email = fields.next()?.to_string();
+            let timestamp = fields.next()?.parse().unwrap_or(0);
+            let parents = fields
+                .next()?
+                .split_whitespace()
+                .map(|p| p.to_string())
+                .collect();
+            let subject = fields.next()?.to_string();
+            let body = fields.next().unwrap_or("").trim_end().to_string();
+            let claude_authored = is_claude_commit(&author_name, &body);
+            Some(CommitInfo {
+                hash,
+                short_hash,
+                author_name,
+                author_email,
+                timestamp,
+                parents,
+                subject,
+                body,
+                claude_authored,
+            })
+        })
+        .collect()
+}
+
+/// Paginated commit history, optionally limited to commits touching `path`
+#[tauri::command]
+pub async fn git_log(
+    project_path: String,
+    path: Option<String>,
+    skip: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<CommitInfo>, String> {
+    tauri::async_runtime::spawn_blocking(move || read_log(&project_path, path.as_deref(), skip, limit))
+        .await
+        .map_err(|e| format!("Failed to read git log: {}", e))?
+}
+
+fn read_log(
+    project_path: &str,
+    path: Option<&str>,
+    skip: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<CommitInfo>, String> {
+    let skip = format!("--skip={}", skip.unwrap_or(0));
+    let limit = format!("--max-count={}", limit.unwrap_or(50));
+    let mut args = vec!["log", LOG_FORMAT, skip.as_str(), limit.as_str()];
+    if let Some(p) = path {
+        args.push("--follow");
+        args.push("--");
+        args.push(p);
+    }
+
+    let output = run_git(project_path, &args)?;
+    Ok(parse_log(&output))
+}
+
+/// Per-line blame for a file, including uncommitted lines in the working tree
+#[tauri::command]
+pub async fn git_blame(project_path: String, path: String) -> Result<BlameResult, String> {
+    tauri::async_runtime::spawn_blocking(move || read_blame(&project_path, &path))
+        .await
+        .map_err(|e| format!("Failed to read git blame: {}", e))?
+}
+
+fn read_blame(project_path: &str, path: &str) -> Result<BlameResult, String> {
+    let output = run_git(project_path, &["blame", "--line-porcelain", "--", path])?;
+
+    let mut lines = Vec::new();
+    let mut uncommitted: Option<CommitInfo> = None;
+    let mut current: Option<(String, usize, usize)> = None;
+    let mut author_name = String::new();
+    let mut author_time = 0;
+
+    for raw in output.lines() {
+        if let Some(content) = raw.strip_prefix('\t') {
+            if let Some((commit, original_line, line)) = current.take() {
+                if commit == UNCOMMITTED_HASH && uncommitted.is_none() {
+                    uncommitted = Some(CommitInfo {
+                        hash: commit.clone(),
+                        short_hash: commit[..7].to_string(),
+                        author_name: author_name.clone(),
+                        author_email: String::new(),
+                        timestamp: author_time,
+                        parents: vec![],
+                        subject: "Not committed yet".to_string(),
+                        body: String::new(),
+                        claude_authored: false,
+                    });
+                }
+                lines.push(BlameLine {
+                    line,
+                    original_line,
+                    commit,
+                    content: content.to_string(),
+                });
+            }
+        } else if let Some(name) = raw.strip_prefix("author ") {
+            author_name = name.to_string();
+        } else if let Some(time) = raw.strip_prefix("author-time ") {
+            author_time = time.parse().unwrap_or(0);
+        } else if current.is_none() {
+            // Header line: "<hash> <original line> <final line> [<group size>]"
+            let mut parts = raw.split_whitespace();
+            if let (Some(hash), Some(orig), Some(fin)) = (parts.next(), parts.next(), parts.next()) {
+                if hash.len() == 40 {
+                    current = Some((
+                        hash.to_string(),
+                        orig.parse().unwrap_or(0),
+                        fin.parse().unwrap_or(0),
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut hashes: Vec<&str> = lines
+        .iter()
+        .map(|l| l.commit.as_str())
+        .filter(|h| *h != UNCOMMITTED_HASH)
+        .collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+
+    let mut commits = HashMap::new();
+    if !hashes.is_empty() {
+        let mut args = vec!["log", "--no-walk=unsorted", LOG_FORMAT];
+        args.extend(hashes);
+        for commit in parse_log(&run_git(project_path, &args)?) {
+            commits.insert(commit.hash.clone(), commit);
+        }
+    }
+    if let Some(commit) = uncommitted {
+        commits.insert(commit.hash.clone(), commit);
+    }
+
+    Ok(BlameResult { lines, commits })
+}
+
+/// A commit's metadata, changed files and patch (optionally limited to one path)
+#[tauri::command]
+pub async fn git_show_commit(
+    project_path: String,
+    commit: String,
+    path: Option<String>,
+) -> Result<CommitDetail, String> {
+    tauri::async_runtime::spawn_blocking(move || read_commit(&project_path, &commit, path.as_deref()))
+        .await
+        .map_err(|e| format!("Failed to read commit: {}", e))?
+}
+
+fn read_commit(project_path: &str, commit: &str, path: Option<&str>) -> Result<CommitDetail, String> {
+    // Resolve first so a commit-ish starting with "-" cannot be taken as an option
+    let hash = run_git(
+        project_path,
+        &["rev-parse", "--verify", "--quiet", "--end-of-options", &format!("{}^{{commit}}", commit)],
+    )
+    .map_err(|_| format!("Commit not found: {}", commit))?;
+    let info = parse_log(&run_git(project_path, &["log", "--no-walk", LOG_FORMAT, hash.trim()])?)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Commit not found: {}", commit))?;
+
+    let name_status = run_git(
+        project_path,
+        &["show", "--no-renames", "--name-status", "-z", "--format=", &info.hash],
+    )?;
+    let numstat = run_git(
+        project_path,
+        &["show", "--no-renames", "--numstat", "-z", "--format=", &info.hash],
+    )?;
+
+    // numstat -z records are "<added>\t<deleted>\t<path>\0"; binary files report "-"
+    let counts: HashMap<&str, (Option<u32>, Option<u32>)> = numstat
+        .split('\0')
+        .filter_map(|record| {
+            let mut parts = record.trim_start_matches('\n').splitn(3, '\t');
+            let added = parts.next()?.parse().ok();
+            let deleted = parts.next()?.parse().ok();
+            Some((parts.next()?, (added, deleted)))
+        })
+        .collect();
+
+    // name-status -z alternates "<status>\0<path>\0"
+    let mut files = Vec::new();
+    let mut fields = name_status.split('\0').map(|f| f.trim_start_matches('\n'));
+    while let (Some(status), Some(file)) = (fields.next(), fields.next()) {
+        if status.is_empty() {
+            break;
+        }
+        let (additions, deletions) = counts.get(file).copied().unwrap_or((None, None));
+        files.push(ChangedFile {
+            path: file.to_string(),
+            status: status.to_string(),
+            additions,
+            deletions,
+        });
+    }
+
+    let mut args = vec!["show", "--no-renames", "--format=", "--patch", info.hash.as_str()];
+    if let Some(p) = path {
+        args.push("--");
+        args.push(p);
+    }
+    let mut diff = run_git(project_path, &args)?;
+    let diff_truncated = diff.len() > MAX_DIFF_BYTES;
+    if diff_truncated {
+        let mut end = MAX_DIFF_BYTES;
+        while !diff.is_char_boundary(end) {
+            end -= 1;
+        }
+        diff.truncate(end);
+    }
+
+    Ok(CommitDetail {
+        commit: info,
+        files,
+        diff,
+        diff_truncated,
+    })
+}
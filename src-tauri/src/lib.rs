@@ -1,23 +1,85 @@
+mod agents;
+mod checkpoint;
 mod claude;
+mod conflicts;
 mod drawer;
+mod emulator;
 mod files;
+mod formatter;
+mod git;
+mod local_history;
+mod lsp;
 mod mcp;
+mod outline;
+mod problems;
+mod recording;
+mod sessions;
+mod shell;
+mod shell_integration;
+mod stats;
+mod symbols;
+mod task_runner;
 mod terminal;
+mod test_explorer;
+mod todos;
+mod watcher;
+mod worktree;
 
+use agents::{discard_agent, list_agents, merge_agent, start_agent, AgentState};
+use checkpoint::{
+    create_checkpoint, delete_checkpoint, list_checkpoints, restore_checkpoint,
+    start_checkpoint_timer, stop_checkpoint_timer, CheckpointState,
+};
 use claude::{
     check_claude_installed, check_mcp_registered, get_claude_project_settings, get_mcp_server_path,
     initialize_project_claude, save_claude_project_settings,
 };
+use conflicts::{list_conflicts, mark_resolved, parse_conflicts, resolve_conflicts};
 use drawer::{list_documents, list_tasks, update_task_status};
-use files::{get_home_dir, read_directory, read_file};
+use files::{get_home_dir, read_directory, read_file, write_file};
+use formatter::{format_buffer, get_formatter};
+use git::{git_blame, git_log, git_show_commit};
+use local_history::{
+    diff_file_version, get_history_retention, list_file_versions, read_file_version,
+    restore_file_version, set_history_retention,
+};
+use lsp::{
+    list_language_servers, lsp_notify, lsp_request, start_language_server, stop_language_server, LspState,
+};
 use mcp::{get_mcp_status, start_mcp_server, stop_mcp_server, McpState};
-use terminal::{create_terminal, kill_terminal, list_terminals, resize_terminal, write_terminal, TerminalState};
+use outline::get_document_outline;
+use problems::{get_problems, list_checkers, run_checkers, set_problems_auto_run, ProblemsState};
+use recording::{delete_recording, list_recordings, play_recording, stop_playback, RecordingState};
+use sessions::{list_sessions, reattach_session, SessionState};
+use shell::{list_terminal_profiles, save_terminal_profiles};
+use stats::get_codebase_stats;
+use symbols::{find_references, find_symbols, go_to_definition, index_project_symbols, SymbolIndexState};
+use task_runner::{list_runnable_tasks, list_task_runs, run_task, TaskRunnerState};
+use terminal::{
+    attach_terminal, create_terminal, get_terminal_history, get_terminal_screen, kill_terminal, list_terminals,
+    resize_terminal, start_recording, stop_recording, write_terminal, TerminalState,
+};
+use test_explorer::{discover_tests, get_test_run, list_test_runs, run_tests, TestExplorerState};
+use todos::{get_todo_markers, import_todos, list_todos, scan_todos, set_todo_markers, TodoState};
+use watcher::{unwatch_project, watch_project, WatcherState};
+use worktree::{
+    create_branch, create_worktree, delete_branch, list_branches, list_worktrees, remove_worktree,
+    switch_branch,
+};
 
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! Welcome to Milhouse.", name)
 }
 
+/// Command-line flag that makes the app binary run as the session daemon
+pub use sessions::DAEMON_ARG;
+
+/// Run as the terminal session daemon instead of the app (see `sessions`)
+pub fn run_session_daemon() {
+    sessions::run_daemon();
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -25,16 +87,41 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(TerminalState::default())
         .manage(McpState::default())
+        .manage(CheckpointState::default())
+        .manage(AgentState::default())
+        .manage(WatcherState::default())
+        .manage(SymbolIndexState::default())
+        .manage(LspState::default())
+        .manage(ProblemsState::default())
+        .manage(TaskRunnerState::default())
+        .manage(TestExplorerState::default())
+        .manage(TodoState::default())
+        .manage(SessionState::default())
+        .manage(RecordingState::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             read_directory,
             read_file,
+            write_file,
             get_home_dir,
             create_terminal,
             write_terminal,
             resize_terminal,
             kill_terminal,
             list_terminals,
+            get_terminal_history,
+            get_terminal_screen,
+            start_recording,
+            stop_recording,
+            list_recordings,
+            delete_recording,
+            play_recording,
+            stop_playback,
+            list_sessions,
+            reattach_session,
+            list_terminal_profiles,
+            save_terminal_profiles,
+            attach_terminal,
             get_claude_project_settings,
             save_claude_project_settings,
             initialize_project_claude,
@@ -46,7 +133,68 @@ pub fn run() {
             get_mcp_status,
             list_tasks,
             list_documents,
-            update_task_status
+            update_task_status,
+            create_checkpoint,
+            list_checkpoints,
+            restore_checkpoint,
+            delete_checkpoint,
+            start_checkpoint_timer,
+            stop_checkpoint_timer,
+            git_log,
+            git_blame,
+            git_show_commit,
+            list_branches,
+            create_branch,
+            switch_branch,
+            delete_branch,
+            list_worktrees,
+            create_worktree,
+            remove_worktree,
+            start_agent,
+            list_agents,
+            merge_agent,
+            discard_agent,
+            watch_project,
+            unwatch_project,
+            list_file_versions,
+            read_file_version,
+            diff_file_version,
+            restore_file_version,
+            get_history_retention,
+            set_history_retention,
+            list_conflicts,
+            parse_conflicts,
+            resolve_conflicts,
+            mark_resolved,
+            get_document_outline,
+            index_project_symbols,
+            find_symbols,
+            go_to_definition,
+            find_references,
+            start_language_server,
+            stop_language_server,
+            list_language_servers,
+            lsp_request,
+            lsp_notify,
+            list_checkers,
+            run_checkers,
+            set_problems_auto_run,
+            get_problems,
+            get_formatter,
+            format_buffer,
+            list_runnable_tasks,
+            run_task,
+            list_task_runs,
+            discover_tests,
+            run_tests,
+            list_test_runs,
+            get_test_run,
+            get_codebase_stats,
+            scan_todos,
+            list_todos,
+            get_todo_markers,
+            set_todo_markers,
+            import_todos
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,376 @@
+use crate::git::create_milhouse_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Local history lives inside the project so it travels with it but is never indexed or watched
+const HISTORY_DIR: &str = ".milhouse/history";
+
+/// Files larger than this are not snapshotted
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Once the stored objects pass this size, the oldest versions across the project are dropped
+const MAX_STORE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Serializes read-modify-write cycles on the history index across the watcher and commands
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileVersion {
+    /// SHA-256 of the content, also the object's name in the store
+    pub hash: String,
+    pub timestamp: i64,
+    pub size: u64,
+    /// What produced the snapshot: "watcher", "write", "restore", ...
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRetention {
+    pub max_versions_per_file: usize,
+    pub max_age_days: u64,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_versions_per_file: 50,
+            max_age_days: 30,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct HistoryIndex {
+    #[serde(default)]
+    retention: HistoryRetention,
+    /// Versions per project-relative path, oldest first
+    #[serde(default)]
+    files: HashMap<String, Vec<FileVersion>>,
+}
+
+fn history_dir(root: &str) -> PathBuf {
+    Path::new(root).join(HISTORY_DIR)
+}
+
+fn object_path(root: &str, hash: &str) -> PathBuf {
+    history_dir(root).join("objects").join(&hash[..2]).join(&hash[2..])
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn load_index(root: &str) -> Result<HistoryIndex, String> {
+    let path = history_dir(root).join("index.json");
+    if !path.exists() {
+        return Ok(HistoryIndex::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read history index: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse history index: {}", e))
+}
+
+fn save_index(root: &str, index: &HistoryIndex) -> Result<(), String> {
+    let dir = history_dir(root);
+    create_milhouse_dir(root, &dir)?;
+    let content =
+        serde_json::to_string(index).map_err(|e| format!("Failed to serialize history index: {}", e))?;
+    // Write then rename so a crash never leaves a half-written index behind
+    let tmp = dir.join("index.json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write history index: {}", e))?;
+    fs::rename(&tmp, dir.join("index.json")).map_err(|e| format!("Failed to write history index: {}", e))
+}
+
+/// Project-relative key for `path`, which may be absolute or already relative
+fn relative_key(root: &str, path: &Path) -> Result<String, String> {
+    let relative = if path.is_absolute() {
+        path.strip_prefix(root)
+            .map_err(|_| format!("Path is outside the project: {}", path.display()))?
+    } else {
+        path
+    };
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Drop versions beyond the retention limits, always keeping the newest one
+fn apply_retention(versions: &mut Vec<FileVersion>, retention: &HistoryRetention) -> Vec<FileVersion> {
+    let cutoff = now_secs() - (retention.max_age_days * 24 * 60 * 60) as i64;
+    let newest = versions.len().saturating_sub(1);
+    let mut removed = Vec::new();
+    let mut kept = Vec::new();
+    for (i, version) in versions.drain(..).enumerate() {
+        if i != newest && version.timestamp < cutoff {
+            removed.push(version);
+        } else {
+            kept.push(version);
+        }
+    }
+    let excess = kept.len().saturating_sub(retention.max_versions_per_file.max(1));
+    removed.extend(kept.drain(..excess));
+    *versions = kept;
+    removed
+}
+
+/// Delete objects for `removed` versions that no file references any more
+fn collect_garbage(root: &str, index: &HistoryIndex, removed: &[FileVersion]) {
+    if removed.is_empty() {
+        return;
+    }
+    let referenced: HashSet<&str> = index
+        .files
+        .values()
+        .flatten()
+        .map(|v| v.hash.as_str())
+        .collect();
+    for version in removed {
+        if !referenced.contains(version.hash.as_str()) {
+            let object = object_path(root, &version.hash);
+            let _ = fs::remove_file(&object);
+            // Fan-out directories are removed once empty
+            if let Some(parent) = object.parent() {
+                let _ = fs::remove_dir(parent);
+            }
+        }
+    }
+}
+
+/// Drop the oldest versions across all files until the objects they reference fit in
+/// `MAX_STORE_BYTES`. A file's newest version only goes once every older version has.
+fn apply_size_cap(index: &mut HistoryIndex) -> Vec<FileVersion> {
+    // Object size and how many versions reference it
+    let mut objects: HashMap<&str, (u64, usize)> = HashMap::new();
+    for version in index.files.values().flatten() {
+        objects.entry(version.hash.as_str()).or_insert((version.size, 0)).1 += 1;
+    }
+    let mut total: u64 = objects.values().map(|(size, _)| size).sum();
+    if total <= MAX_STORE_BYTES {
+        return vec![];
+    }
+
+    let mut candidates: Vec<(bool, i64, &str, usize)> = index
+        .files
+        .iter()
+        .flat_map(|(key, versions)| {
+            versions
+                .iter()
+                .enumerate()
+                .map(move |(i, v)| (i + 1 == versions.len(), v.timestamp, key.as_str(), i))
+        })
+        .collect();
+    candidates.sort();
+
+    // Versions are oldest first, so each file loses a prefix of its list
+    let mut dropped: HashMap<String, usize> = HashMap::new();
+    for (_, _, key, i) in candidates {
+        if total <= MAX_STORE_BYTES {
+            break;
+        }
+        let object = objects.get_mut(index.files[key][i].hash.as_str()).unwrap();
+        object.1 -= 1;
+        if object.1 == 0 {
+            total -= object.0;
+        }
+        *dropped.entry(key.to_string()).or_default() += 1;
+    }
+
+    let mut removed = Vec::new();
+    for (key, count) in dropped {
+        if let Some(versions) = index.files.get_mut(&key) {
+            removed.extend(versions.drain(..count));
+            if versions.is_empty() {
+                index.files.remove(&key);
+            }
+        }
+    }
+    removed
+}
+
+/// Add a snapshot of `path` to a loaded index, returning the new version and the versions
+/// retention dropped for that file
+fn add_version(
+    root: &str,
+    index: &mut HistoryIndex,
+    path: &Path,
+    source: &str,
+) -> Result<Option<(FileVersion, Vec<FileVersion>)>, String> {
+    let absolute = Path::new(root).join(path);
+    let metadata = match fs::metadata(&absolute) {
+        Ok(m) if m.is_file() && m.len() <= MAX_FILE_BYTES => m,
+        _ => return Ok(None),
+    };
+    let content = fs::read(&absolute).map_err(|e| format!("Failed to read file: {}", e))?;
+    let hash = format!("{:x}", Sha256::digest(&content));
+    let key = relative_key(root, path)?;
+
+    let retention = index.retention.clone();
+    let versions = index.files.entry(key).or_default();
+    if versions.last().map(|v| v.hash == hash).unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let object = object_path(root, &hash);
+    if !object.exists() {
+        if let Some(parent) = object.parent() {
+            create_milhouse_dir(root, parent)?;
+        }
+        fs::write(&object, &content).map_err(|e| format!("Failed to write history object: {}", e))?;
+    }
+
+    let version = FileVersion {
+        hash,
+        timestamp: now_secs(),
+        size: metadata.len(),
+        source: source.to_string(),
+    };
+    versions.push(version.clone());
+    let removed = apply_retention(versions, &retention);
+    Ok(Some((version, removed)))
+}
+
+/// Snapshot `path` into the project's local history. Returns `None` when the file
+/// is unchanged since its last snapshot, missing, or too large to keep.
+pub fn record_version(root: &str, path: &Path, source: &str) -> Result<Option<FileVersion>, String> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = load_index(root)?;
+    let Some((version, mut removed)) = add_version(root, &mut index, path, source)? else {
+        return Ok(None);
+    };
+    removed.extend(apply_size_cap(&mut index));
+    save_index(root, &index)?;
+    collect_garbage(root, &index, &removed);
+
+    Ok(Some(version))
+}
+
+/// Snapshot several files with a single index update, e.g. a batch of watcher changes.
+/// Returns how many new versions were recorded.
+pub fn record_versions(root: &str, paths: &[PathBuf], source: &str) -> Result<usize, String> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = load_index(root)?;
+    let mut recorded = 0;
+    let mut removed = Vec::new();
+    for path in paths {
+        match add_version(root, &mut index, path, source) {
+            Ok(Some((_, dropped))) => {
+                recorded += 1;
+                removed.extend(dropped);
+            }
+            Ok(None) => {}
+            Err(e) => println!("[DEBUG] Failed to record history for {}: {}", path.display(), e),
+        }
+    }
+    if recorded == 0 {
+        return Ok(0);
+    }
+    removed.extend(apply_size_cap(&mut index));
+    save_index(root, &index)?;
+    collect_garbage(root, &index, &removed);
+    Ok(recorded)
+}
+
+fn read_object_bytes(root: &str, hash: &str) -> Result<Vec<u8>, String> {
+    if hash.len() < 3 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid version: {}", hash));
+    }
+    fs::read(object_path(root, hash)).map_err(|_| format!("Version not found: {}", hash))
+}
+
+fn read_object(root: &str, hash: &str) -> Result<String, String> {
+    read_object_bytes(root, hash).map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+}
+
+#[tauri::command]
+pub fn list_file_versions(project_path: String, path: String) -> Result<Vec<FileVersion>, String> {
+    let key = relative_key(&project_path, Path::new(&path))?;
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut versions = load_index(&project_path)?.files.remove(&key).unwrap_or_default();
+    versions.reverse();
+    Ok(versions)
+}
+
+#[tauri::command]
+pub fn read_file_version(project_path: String, hash: String) -> Result<String, String> {
+    read_object(&project_path, &hash)
+}
+
+/// Unified diff from version `hash` to `against` (another version), or to the file on disk
+#[tauri::command]
+pub fn diff_file_version(
+    project_path: String,
+    path: String,
+    hash: String,
+    against: Option<String>,
+) -> Result<String, String> {
+    let old = read_object(&project_path, &hash)?;
+    let (new, new_label) = match against {
+        Some(ref other) => (read_object(&project_path, other)?, other[..8.min(other.len())].to_string()),
+        None => (
+            fs::read_to_string(Path::new(&project_path).join(&path)).unwrap_or_default(),
+            "working copy".to_string(),
+        ),
+    };
+    let key = relative_key(&project_path, Path::new(&path))?;
+    let old_label = format!("{} ({})", key, &hash[..8.min(hash.len())]);
+    let new_label = format!("{} ({})", key, new_label);
+
+    Ok(TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .header(&old_label, &new_label)
+        .to_string())
+}
+
+/// Overwrite the file with version `hash`, snapshotting the current content first
+#[tauri::command]
+pub fn restore_file_version(project_path: String, path: String, hash: String) -> Result<(), String> {
+    let relative = Path::new(&path);
+    // Only a plain relative path stays inside the project
+    if relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Path is outside the project: {}", path));
+    }
+    let content = read_object_bytes(&project_path, &hash)?;
+    record_version(&project_path, relative, "before-restore")?;
+
+    let absolute = Path::new(&project_path).join(relative);
+    if let Some(parent) = absolute.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&absolute, content).map_err(|e| format!("Failed to write file: {}", e))?;
+    record_version(&project_path, relative, "restore")?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_history_retention(project_path: String) -> Result<HistoryRetention, String> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    Ok(load_index(&project_path)?.retention)
+}
+
+#[tauri::command]
+pub fn set_history_retention(project_path: String, retention: HistoryRetention) -> Result<(), String> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = load_index(&project_path)?;
+    index.retention = retention;
+
+    let mut removed = Vec::new();
+    let retention = index.retention.clone();
+    for versions in index.files.values_mut() {
+        removed.extend(apply_retention(versions, &retention));
+    }
+    index.files.retain(|_, versions| !versions.is_empty());
+    save_index(&project_path, &index)?;
+    collect_garbage(&project_path, &index, &removed);
+    Ok(())
+}
@@ -0,0 +1,473 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{oneshot, watch};
+
+/// How long to wait for a server to answer `initialize`
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default timeout for requests relayed from the frontend
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long `shutdown` may take before the server is killed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Per-project overrides live in this file, keyed by language id
+const PROJECT_CONFIG_PATH: &str = ".milhouse/lsp.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LspServerConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+    /// Options sent as `initializationOptions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initialization_options: Option<Value>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LspServerInfo {
+    pub id: String,
+    pub language: String,
+    pub root: String,
+    pub command: String,
+    pub pid: u32,
+    pub running: bool,
+    /// Capabilities the server announced in its `initialize` response
+    pub capabilities: Value,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LspNotification {
+    pub server_id: String,
+    pub method: String,
+    pub params: Value,
+}
+
+type PendingRequests = Mutex<HashMap<i64, oneshot::Sender<Result<Value, Value>>>>;
+
+struct LspServer {
+    info: Mutex<LspServerInfo>,
+    child: Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    next_id: AtomicI64,
+    pending: PendingRequests,
+    /// Outcome of `initialize`, for calls that find the server still starting
+    ready: watch::Sender<Option<Result<(), String>>>,
+}
+
+/// Running language servers, keyed by "<language>:<project root>"
+#[derive(Default)]
+pub struct LspState {
+    servers: Mutex<HashMap<String, Arc<LspServer>>>,
+}
+
+/// Built-in server commands for each supported language id
+fn default_config(language: &str) -> Option<LspServerConfig> {
+    let (command, args): (&str, &[&str]) = match language {
+        "rust" => ("rust-analyzer", &[]),
+        "typescript" | "javascript" | "typescriptreact" | "javascriptreact" => {
+            ("typescript-language-server", &["--stdio"])
+        }
+        "python" => ("pyright-langserver", &["--stdio"]),
+        "go" => ("gopls", &[]),
+        _ => return None,
+    };
+    Some(LspServerConfig {
+        command: command.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        env: None,
+        initialization_options: None,
+    })
+}
+
+/// Server configuration for `language`, preferring the project's `.milhouse/lsp.json`
+fn resolve_config(root: &str, language: &str) -> Result<LspServerConfig, String> {
+    let config_path = Path::new(root).join(PROJECT_CONFIG_PATH);
+    if config_path.exists() {
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read LSP config: {}", e))?;
+        let mut configs: HashMap<String, LspServerConfig> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse LSP config: {}", e))?;
+        if let Some(config) = configs.remove(language) {
+            return Ok(config);
+        }
+    }
+    default_config(language).ok_or_else(|| format!("No language server configured for: {}", language))
+}
+
+/// Convert a filesystem path to a `file://` URI
+pub fn path_to_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let encoded: String = path
+        .chars()
+        .map(|c| match c {
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            '?' => "%3F".to_string(),
+            '%' => "%25".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+impl LspServer {
+    fn id(&self) -> String {
+        self.info.lock().unwrap().id.clone()
+    }
+
+    /// Write one message with LSP's Content-Length framing
+    fn send(&self, message: &Value) -> Result<(), String> {
+        let body = message.to_string();
+        let mut stdin = self.stdin.lock().unwrap();
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to language server: {}", e))
+    }
+
+    fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        if let Err(e) = self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })) {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(format!("{} failed: {}", method, error)),
+            Ok(Err(_)) => Err("Language server exited".to_string()),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                // Tell the server we no longer care about the answer
+                let _ = self.notify("$/cancelRequest", json!({ "id": id }));
+                Err(format!("{} timed out", method))
+            }
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.child.lock().unwrap().try_wait(), Ok(None))
+    }
+}
+
+/// Read one Content-Length framed message body; `None` on EOF or a broken stream
+fn read_message(reader: &mut impl BufRead) -> Option<Vec<u8>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length?];
+    reader.read_exact(&mut body).ok()?;
+    Some(body)
+}
+
+/// Answer requests the server sends to us; we support just enough for common servers
+fn respond_to_server_request(server: &LspServer, id: Value, method: &str, params: &Value) {
+    let result = match method {
+        // One (empty) configuration section per requested item
+        "workspace/configuration" => {
+            let count = params["items"].as_array().map(|items| items.len()).unwrap_or(0);
+            Value::Array(vec![Value::Null; count])
+        }
+        "workspace/workspaceFolders" => {
+            let root = server.info.lock().unwrap().root.clone();
+            json!([{ "uri": path_to_uri(&root), "name": root }])
+        }
+        _ => Value::Null,
+    };
+    let _ = server.send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn spawn_reader(app: AppHandle, server: Arc<LspServer>, stdout: impl Read + Send + 'static) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        let server_id = server.id();
+
+        while let Some(body) = read_message(&mut reader) {
+            let Ok(message) = serde_json::from_slice::<Value>(&body) else {
+                continue;
+            };
+            let method = message.get("method").and_then(|m| m.as_str());
+            match (message.get("id"), method) {
+                // Response to one of our requests
+                (Some(id), None) => {
+                    let Some(id) = id.as_i64() else { continue };
+                    if let Some(sender) = server.pending.lock().unwrap().remove(&id) {
+                        let result = match message.get("error") {
+                            Some(error) => Err(error.clone()),
+                            None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                        };
+                        let _ = sender.send(result);
+                    }
+                }
+                (Some(id), Some(method)) => {
+                    let params = message.get("params").cloned().unwrap_or(Value::Null);
+                    respond_to_server_request(&server, id.clone(), method, &params);
+                }
+                (None, Some(method)) => {
+                    let _ = app.emit(
+                        "lsp-notification",
+                        LspNotification {
+                            server_id: server_id.clone(),
+                            method: method.to_string(),
+                            params: message.get("params").cloned().unwrap_or(Value::Null),
+                        },
+                    );
+                }
+                (None, None) => {}
+            }
+        }
+
+        println!("[DEBUG] Language server {} exited", server_id);
+        // Dropping the senders fails any requests still waiting
+        server.pending.lock().unwrap().clear();
+        server.info.lock().unwrap().running = false;
+        forget_server(&app.state::<LspState>(), &server);
+        let _ = app.emit("lsp-exit", server_id);
+    });
+}
+
+/// Remove `server` from the running servers, unless another server has replaced it since
+fn forget_server(state: &LspState, server: &Arc<LspServer>) {
+    let server_id = server.id();
+    let mut servers = state.servers.lock().unwrap();
+    if servers.get(&server_id).is_some_and(|s| Arc::ptr_eq(s, server)) {
+        servers.remove(&server_id);
+    }
+}
+
+fn find_server(state: &LspState, server_id: &str) -> Result<Arc<LspServer>, String> {
+    state
+        .servers
+        .lock()
+        .unwrap()
+        .get(server_id)
+        .cloned()
+        .ok_or_else(|| format!("Language server not found: {}", server_id))
+}
+
+/// The running server for `server_id`, or a newly spawned one with its config. Checking and
+/// registering under one lock keeps concurrent calls from starting the same server twice.
+fn launch_server(
+    app: &AppHandle,
+    state: &LspState,
+    server_id: &str,
+    project_path: &str,
+    language: &str,
+) -> Result<(Arc<LspServer>, Option<LspServerConfig>), String> {
+    let mut servers = state.servers.lock().unwrap();
+    if let Some(existing) = servers.get(server_id).filter(|s| s.is_running()) {
+        return Ok((existing.clone(), None));
+    }
+
+    let config = resolve_config(project_path, language)?;
+    let mut child = {
+        let mut command = Command::new(&config.command);
+        command
+            .args(&config.args)
+            .current_dir(project_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref env) = config.env {
+            command.envs(env);
+        }
+        command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", config.command, e))?
+    };
+
+    let stdin = child.stdin.take().ok_or_else(|| "Failed to open server stdin".to_string())?;
+    let stdout = child.stdout.take().ok_or_else(|| "Failed to open server stdout".to_string())?;
+    let stderr = child.stderr.take();
+
+    let server = Arc::new(LspServer {
+        info: Mutex::new(LspServerInfo {
+            id: server_id.to_string(),
+            language: language.to_string(),
+            root: project_path.to_string(),
+            command: config.command.clone(),
+            pid: child.id(),
+            running: true,
+            capabilities: Value::Null,
+        }),
+        child: Mutex::new(child),
+        stdin: Mutex::new(stdin),
+        next_id: AtomicI64::new(1),
+        pending: Mutex::new(HashMap::new()),
+        ready: watch::Sender::new(None),
+    });
+    servers.insert(server_id.to_string(), server.clone());
+    drop(servers);
+
+    spawn_reader(app.clone(), server.clone(), stdout);
+    if let Some(stderr) = stderr {
+        let label = server_id.to_string();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                println!("[DEBUG] [{}] {}", label, line);
+            }
+        });
+    }
+    Ok((server, Some(config)))
+}
+
+/// Launch (or reuse) the language server for `language` in a project and initialize it
+#[tauri::command]
+pub async fn start_language_server(
+    app: AppHandle,
+    state: State<'_, LspState>,
+    project_path: String,
+    language: String,
+) -> Result<LspServerInfo, String> {
+    let server_id = format!("{}:{}", language, project_path);
+    let (server, config) = launch_server(&app, &state, &server_id, &project_path, &language)?;
+    let Some(config) = config else {
+        // Started by another call, which may still be initializing it
+        let mut ready = server.ready.subscribe();
+        let outcome = ready
+            .wait_for(|outcome| outcome.is_some())
+            .await
+            .map(|outcome| outcome.clone())
+            .map_err(|_| "Language server exited".to_string())?;
+        outcome.unwrap_or(Ok(()))?;
+        return Ok(server.info.lock().unwrap().clone());
+    };
+    let pid = server.info.lock().unwrap().pid;
+
+    let root_uri = path_to_uri(&project_path);
+    let params = json!({
+        "processId": std::process::id(),
+        "clientInfo": { "name": "Milhouse", "version": env!("CARGO_PKG_VERSION") },
+        "rootUri": root_uri,
+        "rootPath": project_path,
+        "workspaceFolders": [{ "uri": root_uri, "name": project_path }],
+        "initializationOptions": config.initialization_options,
+        "capabilities": {
+            "textDocument": {
+                "synchronization": { "didSave": true, "dynamicRegistration": false },
+                "hover": { "contentFormat": ["markdown", "plaintext"] },
+                "completion": { "completionItem": { "snippetSupport": false } },
+                "definition": { "linkSupport": true },
+                "references": {},
+                "documentSymbol": { "hierarchicalDocumentSymbolSupport": true },
+                "publishDiagnostics": { "relatedInformation": true }
+            },
+            "workspace": { "configuration": true, "workspaceFolders": true },
+            "window": { "workDoneProgress": true }
+        }
+    });
+
+    let initialized = server.request("initialize", params, INITIALIZE_TIMEOUT).await;
+    let result = match initialized.and_then(|result| server.notify("initialized", json!({})).map(|_| result)) {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = server.child.lock().unwrap().kill();
+            forget_server(&state, &server);
+            server.ready.send_replace(Some(Err(e.clone())));
+            return Err(e);
+        }
+    };
+
+    let info = {
+        let mut info = server.info.lock().unwrap();
+        info.capabilities = result.get("capabilities").cloned().unwrap_or(Value::Null);
+        info.clone()
+    };
+    server.ready.send_replace(Some(Ok(())));
+
+    println!("[DEBUG] Started language server {} (pid {})", info.command, pid);
+    Ok(info)
+}
+
+/// Shut a server down politely, killing it if it does not exit in time
+#[tauri::command]
+pub async fn stop_language_server(state: State<'_, LspState>, server_id: String) -> Result<(), String> {
+    let server = find_server(&state, &server_id)?;
+    forget_server(&state, &server);
+
+    let _ = server.request("shutdown", Value::Null, SHUTDOWN_TIMEOUT).await;
+    let _ = server.notify("exit", Value::Null);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let mut child = server.child.lock().unwrap();
+    if let Ok(None) = child.try_wait() {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_language_servers(state: State<'_, LspState>) -> Vec<LspServerInfo> {
+    let servers = state.servers.lock().unwrap();
+    servers
+        .values()
+        .map(|server| {
+            let mut info = server.info.lock().unwrap().clone();
+            info.running = server.is_running();
+            info
+        })
+        .collect()
+}
+
+/// Relay a request (hover, completion, definition, ...) and return the server's result
+#[tauri::command]
+pub async fn lsp_request(
+    state: State<'_, LspState>,
+    server_id: String,
+    method: String,
+    params: Value,
+    timeout_ms: Option<u64>,
+) -> Result<Value, String> {
+    let server = find_server(&state, &server_id)?;
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(REQUEST_TIMEOUT);
+    server.request(&method, params, timeout).await
+}
+
+/// Relay a notification (didOpen, didChange, didSave, didClose, ...)
+#[tauri::command]
+pub fn lsp_notify(
+    state: State<'_, LspState>,
+    server_id: String,
+    method: String,
+    params: Value,
+) -> Result<(), String> {
+    find_server(&state, &server_id)?.notify(&method, params)
+}
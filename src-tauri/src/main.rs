@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+  // The same binary doubles as the persistent terminal session daemon
+  if std::env::args().any(|arg| arg == app_lib::DAEMON_ARG) {
+    app_lib::run_session_daemon();
+    return;
+  }
   app_lib::run();
 }
@@ -0,0 +1,286 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser};
+
+/// Files larger than this are not parsed
+pub const MAX_OUTLINE_BYTES: usize = 2 * 1024 * 1024;
+
+/// Longest `detail` string returned for a symbol
+const MAX_DETAIL_CHARS: usize = 120;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum OutlineLanguage {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+    Go,
+    Markdown,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SymbolRange {
+    /// 1-based line numbers, 0-based byte columns
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct OutlineSymbol {
+    pub name: String,
+    /// "function", "method", "struct", "enum", "trait", "impl", "class", "interface",
+    /// "type", "module", "constant", "macro" or "heading"
+    pub kind: String,
+    pub range: SymbolRange,
+    /// Range of the symbol's name, the jump target for go-to-definition
+    pub selection_range: SymbolRange,
+    /// First line of the declaration, e.g. a function signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub children: Vec<OutlineSymbol>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DocumentOutline {
+    pub language: OutlineLanguage,
+    pub symbols: Vec<OutlineSymbol>,
+}
+
+impl OutlineLanguage {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            // The TSX grammar is a superset that also handles plain JavaScript
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Markdown => tree_sitter_md::LANGUAGE.into(),
+        }
+    }
+}
+
+fn range_of(node: Node) -> SymbolRange {
+    let (start, end) = (node.start_position(), node.end_position());
+    SymbolRange {
+        start_line: start.row + 1,
+        start_column: start.column,
+        end_line: end.row + 1,
+        end_column: end.column,
+    }
+}
+
+fn text<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or("")
+}
+
+fn first_line(node: Node, source: &str) -> Option<String> {
+    let line = text(node, source).lines().next()?.trim();
+    let line = line.trim_end_matches('{').trim_end();
+    if line.is_empty() {
+        None
+    } else {
+        Some(line.chars().take(MAX_DETAIL_CHARS).collect())
+    }
+}
+
+/// Decide whether `node` is an outline symbol, returning its kind and name node
+fn classify<'t>(
+    language: OutlineLanguage,
+    node: Node<'t>,
+    parent_kind: Option<&str>,
+) -> Option<(&'static str, Node<'t>)> {
+    let name = node.child_by_field_name("name");
+    let in_type = matches!(parent_kind, Some("impl" | "trait" | "class" | "interface"));
+    let function_kind = if in_type { "method" } else { "function" };
+
+    let kind = match (language, node.kind()) {
+        (OutlineLanguage::Rust, "function_item" | "function_signature_item") => function_kind,
+        (OutlineLanguage::Rust, "struct_item" | "union_item") => "struct",
+        (OutlineLanguage::Rust, "enum_item") => "enum",
+        (OutlineLanguage::Rust, "trait_item") => "trait",
+        (OutlineLanguage::Rust, "mod_item") => "module",
+        (OutlineLanguage::Rust, "type_item") => "type",
+        (OutlineLanguage::Rust, "const_item" | "static_item") => "constant",
+        (OutlineLanguage::Rust, "macro_definition") => "macro",
+        // impl blocks have no name; the implemented type stands in for it
+        (OutlineLanguage::Rust, "impl_item") => return Some(("impl", node.child_by_field_name("type")?)),
+
+        (OutlineLanguage::TypeScript | OutlineLanguage::Tsx, kind) => match kind {
+            "function_declaration" | "generator_function_declaration" | "function_signature" => "function",
+            "class_declaration" | "abstract_class_declaration" => "class",
+            "method_definition" | "method_signature" | "abstract_method_signature" => "method",
+            "interface_declaration" => "interface",
+            "type_alias_declaration" => "type",
+            "enum_declaration" => "enum",
+            "internal_module" | "module" => "module",
+            // `const foo = () => {}` and `const Foo = class {}`
+            "variable_declarator" => match node.child_by_field_name("value")?.kind() {
+                "arrow_function" | "function_expression" | "function" | "generator_function" => "function",
+                "class" => "class",
+                _ => return None,
+            },
+            _ => return None,
+        },
+
+        (OutlineLanguage::Python, "function_definition") => function_kind,
+        (OutlineLanguage::Python, "class_definition") => "class",
+
+        (OutlineLanguage::Go, "function_declaration") => "function",
+        (OutlineLanguage::Go, "method_declaration") => "method",
+        (OutlineLanguage::Go, "type_spec") => match node.child_by_field_name("type")?.kind() {
+            "struct_type" => "struct",
+            "interface_type" => "interface",
+            _ => "type",
+        },
+        (OutlineLanguage::Go, "const_spec") if parent_kind.is_none() => "constant",
+
+        // Sections nest by heading level and span the heading's content
+        (OutlineLanguage::Markdown, "section") => {
+            let mut cursor = node.walk();
+            let heading = node
+                .named_children(&mut cursor)
+                .find(|c| matches!(c.kind(), "atx_heading" | "setext_heading"))?;
+            let content = heading.child_by_field_name("heading_content").unwrap_or(heading);
+            return Some(("heading", content));
+        }
+
+        _ => return None,
+    };
+
+    Some((kind, name?))
+}
+
+fn collect_symbols(
+    language: OutlineLanguage,
+    node: Node,
+    source: &str,
+    parent_kind: Option<&str>,
+    out: &mut Vec<OutlineSymbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match classify(language, child, parent_kind) {
+            Some((kind, name_node)) => {
+                let mut children = Vec::new();
+                collect_symbols(language, child, source, Some(kind), &mut children);
+                let name = match (language, child.kind()) {
+                    (OutlineLanguage::Rust, "impl_item") => match child.child_by_field_name("trait") {
+                        Some(t) => format!("{} for {}", text(t, source), text(name_node, source)),
+                        None => text(name_node, source).to_string(),
+                    },
+                    _ => text(name_node, source).trim().to_string(),
+                };
+                out.push(OutlineSymbol {
+                    name,
+                    kind: kind.to_string(),
+                    range: range_of(child),
+                    selection_range: range_of(name_node),
+                    detail: if kind == "heading" { None } else { first_line(child, source) },
+                    children,
+                });
+            }
+            None => collect_symbols(language, child, source, parent_kind, out),
+        }
+    }
+}
+
+/// Parse `source` and extract its symbol outline
+pub fn outline_source(language: OutlineLanguage, source: &str) -> Result<Vec<OutlineSymbol>, String> {
+    let tree = parse(language, source).ok_or_else(|| "Failed to parse file".to_string())?;
+
+    let mut symbols = Vec::new();
+    collect_symbols(language, tree.root_node(), source, None, &mut symbols);
+    Ok(symbols)
+}
+
+/// Outline of a file on disk, or of unsaved editor `content` for that path
+#[tauri::command]
+pub fn get_document_outline(path: String, content: Option<String>) -> Result<DocumentOutline, String> {
+    let language = OutlineLanguage::from_path(Path::new(&path))
+        .ok_or_else(|| format!("No outline support for: {}", path))?;
+
+    let source = match content {
+        Some(content) => content,
+        None => fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+    if source.len() > MAX_OUTLINE_BYTES {
+        return Err(format!("File is too large to outline: {}", path));
+    }
+
+    Ok(DocumentOutline {
+        language,
+        symbols: outline_source(language, &source)?,
+    })
+}
+
+/// Whether a syntax node kind names something (as opposed to a keyword, string or comment)
+fn is_identifier_kind(kind: &str) -> bool {
+    kind.ends_with("identifier") || kind == "constant" || kind == "metavariable"
+}
+
+fn parse(language: OutlineLanguage, source: &str) -> Option<tree_sitter::Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    parser.parse(source, None)
+}
+
+/// The identifier under a 1-based line and 0-based byte column, if any
+pub fn identifier_at(language: OutlineLanguage, source: &str, line: usize, column: usize) -> Option<String> {
+    let tree = parse(language, source)?;
+    let point = tree_sitter::Point {
+        row: line.checked_sub(1)?,
+        column,
+    };
+    let node = tree.root_node().named_descendant_for_point_range(point, point)?;
+    if is_identifier_kind(node.kind()) {
+        Some(text(node, source).to_string())
+    } else {
+        None
+    }
+}
+
+/// Ranges of every identifier token spelled `name`, skipping comments and string literals
+pub fn find_identifier_ranges(language: OutlineLanguage, source: &str, name: &str) -> Vec<SymbolRange> {
+    let Some(tree) = parse(language, source) else {
+        return vec![];
+    };
+
+    let mut ranges = Vec::new();
+    let mut cursor = tree.walk();
+    let mut visited_children = false;
+    loop {
+        let node = cursor.node();
+        if !visited_children && node.child_count() == 0 && is_identifier_kind(node.kind()) && text(node, source) == name {
+            ranges.push(range_of(node));
+        }
+        if !visited_children && cursor.goto_first_child() {
+            continue;
+        }
+        if cursor.goto_next_sibling() {
+            visited_children = false;
+        } else if cursor.goto_parent() {
+            visited_children = true;
+        } else {
+            break;
+        }
+    }
+    ranges
+}
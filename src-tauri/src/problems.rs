@@ -0,0 +1,606 @@
+use crate::outline::SymbolRange;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Per-project checker overrides
+const PROJECT_CONFIG_PATH: &str = ".milhouse/problems.json";
+
+/// Quiet period after the last file change before checkers re-run automatically
+const AUTO_RUN_DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Checkers taking longer than this are killed; a cold `cargo check` can take minutes
+const DEFAULT_TIMEOUT_MS: u64 = 600_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CheckerConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Output format: "cargo", "tsc" or "eslint"
+    pub parser: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Diagnostic {
+    /// Project-relative path with forward slashes
+    pub path: String,
+    pub range: SymbolRange,
+    /// "error", "warning", "info" or "hint"
+    pub severity: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Name of the checker that reported it
+    pub source: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct FileProblemCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CheckerResult {
+    pub project_path: String,
+    pub checker: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub duration_ms: u64,
+    /// Set when the checker could not be run at all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProblemsSummary {
+    pub diagnostics: Vec<Diagnostic>,
+    /// Error and warning counts per path, for flagging files in the tree
+    pub files: HashMap<String, FileProblemCounts>,
+    /// Checkers currently running
+    pub running: Vec<String>,
+}
+
+#[derive(Default)]
+struct ProjectProblems {
+    results: HashMap<String, CheckerResult>,
+    running: HashSet<String>,
+    auto_run: bool,
+    /// Bumped on every file change so only the last debounce timer fires
+    change_generation: u64,
+}
+
+#[derive(Default)]
+pub struct ProblemsState {
+    projects: Mutex<HashMap<String, ProjectProblems>>,
+}
+
+fn checker(name: &str, command: &str, args: &[&str], parser: &str) -> CheckerConfig {
+    CheckerConfig {
+        name: name.to_string(),
+        command: command.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        parser: parser.to_string(),
+        timeout_ms: None,
+    }
+}
+
+/// Checkers for a project: `.milhouse/problems.json` if present, otherwise detected from project files
+fn resolve_checkers(root: &str) -> Result<Vec<CheckerConfig>, String> {
+    let root_path = Path::new(root);
+    let config_path = root_path.join(PROJECT_CONFIG_PATH);
+    if config_path.exists() {
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read problems config: {}", e))?;
+        return serde_json::from_str(&content).map_err(|e| format!("Failed to parse problems config: {}", e));
+    }
+
+    let mut checkers = Vec::new();
+    if root_path.join("Cargo.toml").exists() {
+        checkers.push(checker("cargo", "cargo", &["check", "--workspace", "--all-targets", "--message-format=json"], "cargo"));
+    }
+    if root_path.join("tsconfig.json").exists() {
+        checkers.push(checker("tsc", "npx", &["--no-install", "tsc", "--noEmit", "--pretty", "false"], "tsc"));
+    }
+    let has_eslint_config = fs::read_dir(root_path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|e| e.file_name().to_string_lossy().starts_with(".eslintrc") || e.file_name().to_string_lossy().starts_with("eslint.config"))
+        })
+        .unwrap_or(false);
+    if has_eslint_config {
+        checkers.push(checker("eslint", "npx", &["--no-install", "eslint", "--format", "json", "."], "eslint"));
+    }
+    Ok(checkers)
+}
+
+/// Make a tool-reported path project-relative when it lies inside the project
+fn relative_path(root: &str, path: &str) -> String {
+    let absolute = Path::new(root).join(path);
+    let relative = absolute.strip_prefix(root).unwrap_or(&absolute);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Convert 1-based tool positions to our 1-based line, 0-based column ranges
+fn tool_range(line: u64, column: u64, end_line: u64, end_column: u64) -> SymbolRange {
+    SymbolRange {
+        start_line: line.max(1) as usize,
+        start_column: column.saturating_sub(1) as usize,
+        end_line: end_line.max(line).max(1) as usize,
+        end_column: end_column.saturating_sub(1) as usize,
+    }
+}
+
+/// Parse `cargo --message-format=json` output
+fn parse_cargo(root: &str, source: &str, output: &str) -> Vec<Diagnostic> {
+    let mut seen = HashSet::new();
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|v| v["reason"] == "compiler-message")
+        .filter_map(|v| {
+            let message = &v["message"];
+            let span = message["spans"].as_array()?.iter().find(|s| s["is_primary"] == true)?;
+            let severity = match message["level"].as_str()? {
+                "error" | "error: internal compiler error" => "error",
+                "warning" => "warning",
+                "note" => "info",
+                _ => "hint",
+            };
+            let diagnostic = Diagnostic {
+                path: relative_path(root, span["file_name"].as_str()?),
+                range: tool_range(
+                    span["line_start"].as_u64()?,
+                    span["column_start"].as_u64()?,
+                    span["line_end"].as_u64()?,
+                    span["column_end"].as_u64()?,
+                ),
+                severity: severity.to_string(),
+                message: message["message"].as_str()?.to_string(),
+                code: message["code"]["code"].as_str().map(|c| c.to_string()),
+                source: source.to_string(),
+            };
+            // The same diagnostic is reported once per target (lib, bin, tests)
+            let key = format!("{}:{}:{}:{}", diagnostic.path, diagnostic.range.start_line, diagnostic.range.start_column, diagnostic.message);
+            seen.insert(key).then_some(diagnostic)
+        })
+        .collect()
+}
+
+/// Parse `tsc --pretty false` output: `path(line,col): error TS1234: message`
+fn parse_tsc(root: &str, source: &str, output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in output.lines() {
+        // Indented lines continue the previous message
+        if line.starts_with(' ') {
+            if let Some(last) = diagnostics.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(line.trim());
+            }
+            continue;
+        }
+        let Some((location, rest)) = line.split_once("): ") else {
+            continue;
+        };
+        let Some((path, position)) = location.rsplit_once('(') else {
+            continue;
+        };
+        let mut numbers = position.split(',').filter_map(|n| n.trim().parse::<u64>().ok());
+        let (Some(line_number), Some(column)) = (numbers.next(), numbers.next()) else {
+            continue;
+        };
+        let Some((header, message)) = rest.split_once(": ") else {
+            continue;
+        };
+        let mut header = header.split_whitespace();
+        let severity = match header.next() {
+            Some("error") => "error",
+            Some("warning") => "warning",
+            _ => "info",
+        };
+        diagnostics.push(Diagnostic {
+            path: relative_path(root, path),
+            range: tool_range(line_number, column, line_number, column),
+            severity: severity.to_string(),
+            message: message.to_string(),
+            code: header.next().map(|c| c.to_string()),
+            source: source.to_string(),
+        });
+    }
+    diagnostics
+}
+
+/// Parse `eslint --format json` output
+fn parse_eslint(root: &str, source: &str, output: &str) -> Vec<Diagnostic> {
+    let Ok(files) = serde_json::from_str::<Vec<Value>>(output.trim()) else {
+        return vec![];
+    };
+    files
+        .iter()
+        .flat_map(|file| {
+            let path = relative_path(root, file["filePath"].as_str().unwrap_or(""));
+            file["messages"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |m| {
+                    let line = m["line"].as_u64().unwrap_or(1);
+                    let column = m["column"].as_u64().unwrap_or(1);
+                    Diagnostic {
+                        path: path.clone(),
+                        range: tool_range(
+                            line,
+                            column,
+                            m["endLine"].as_u64().unwrap_or(line),
+                            m["endColumn"].as_u64().unwrap_or(column),
+                        ),
+                        severity: if m["severity"] == 2 { "error" } else { "warning" }.to_string(),
+                        message: m["message"].as_str().unwrap_or("").to_string(),
+                        code: m["ruleId"].as_str().map(|r| r.to_string()),
+                        source: source.to_string(),
+                    }
+                })
+        })
+        .collect()
+}
+
+/// Run a checker and parse its report, killing it after the timeout
+fn run_checker(root: &str, config: &CheckerConfig) -> CheckerResult {
+    let started = Instant::now();
+    let mut child = match Command::new(&config.command)
+        .args(&config.args)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return failed(root, config, started, format!("Failed to run {}: {}", config.command, e)),
+    };
+
+    // Drain the pipes on threads so a large report cannot deadlock
+    let mut stdout = child.stdout.take().unwrap();
+    let stdout_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    let timeout = Duration::from_millis(config.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                let error = format!("{} timed out after {}s", config.command, timeout.as_secs());
+                return failed(root, config, started, error);
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return failed(root, config, started, format!("Failed to wait for {}: {}", config.command, e)),
+        }
+    };
+
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stdout = String::from_utf8_lossy(&stdout);
+    let diagnostics = match config.parser.as_str() {
+        "cargo" => parse_cargo(root, &config.name, &stdout),
+        "tsc" => parse_tsc(root, &config.name, &stdout),
+        "eslint" => parse_eslint(root, &config.name, &stdout),
+        other => return failed(root, config, started, format!("Unknown parser: {}", other)),
+    };
+    // A failing run with nothing parsed means the tool itself broke
+    let error = if !status.success() && diagnostics.is_empty() {
+        let stderr = stderr_reader.join().unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr);
+        Some(stderr.lines().last().unwrap_or("Checker failed").to_string())
+    } else {
+        None
+    };
+
+    CheckerResult {
+        project_path: root.to_string(),
+        checker: config.name.clone(),
+        diagnostics,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+fn failed(root: &str, config: &CheckerConfig, started: Instant, error: String) -> CheckerResult {
+    CheckerResult {
+        project_path: root.to_string(),
+        checker: config.name.clone(),
+        diagnostics: vec![],
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: Some(error),
+    }
+}
+
+/// Marks a checker as no longer running when dropped, even if its run panicked
+struct RunningChecker {
+    app: AppHandle,
+    root: String,
+    name: String,
+}
+
+impl Drop for RunningChecker {
+    fn drop(&mut self) {
+        let state = self.app.state::<ProblemsState>();
+        let mut projects = state.projects.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(project) = projects.get_mut(&self.root) {
+            project.running.remove(&self.name);
+        }
+    }
+}
+
+/// Run checkers on background threads, emitting `problems-updated` as each one finishes
+fn start_checkers(app: &AppHandle, root: &str, only: Option<&[String]>) -> Result<Vec<String>, String> {
+    let checkers: Vec<CheckerConfig> = resolve_checkers(root)?
+        .into_iter()
+        .filter(|c| only.map(|names| names.contains(&c.name)).unwrap_or(true))
+        .collect();
+
+    let state = app.state::<ProblemsState>();
+    let mut started = Vec::new();
+    for config in checkers {
+        {
+            let mut projects = state.projects.lock().unwrap();
+            let project = projects.entry(root.to_string()).or_default();
+            // Never run two copies of the same checker at once
+            if !project.running.insert(config.name.clone()) {
+                continue;
+            }
+        }
+        started.push(config.name.clone());
+
+        let app = app.clone();
+        let root = root.to_string();
+        thread::spawn(move || {
+            let running = RunningChecker {
+                app: app.clone(),
+                root: root.clone(),
+                name: config.name.clone(),
+            };
+            let result = run_checker(&root, &config);
+            println!(
+                "[DEBUG] Checker {} finished with {} diagnostics in {}ms",
+                config.name,
+                result.diagnostics.len(),
+                result.duration_ms
+            );
+            {
+                let state = app.state::<ProblemsState>();
+                let mut projects = state.projects.lock().unwrap();
+                let project = projects.entry(root.clone()).or_default();
+                project.results.insert(config.name.clone(), result.clone());
+            }
+            drop(running);
+            let _ = app.emit("problems-updated", result);
+        });
+    }
+    Ok(started)
+}
+
+/// Called by the file watcher; re-runs checkers after a quiet period when auto-run is on
+pub fn schedule_auto_run(app: &AppHandle, root: &str) {
+    let generation = {
+        let state = app.state::<ProblemsState>();
+        let mut projects = state.projects.lock().unwrap();
+        match projects.get_mut(root) {
+            Some(project) if project.auto_run => {
+                project.change_generation += 1;
+                project.change_generation
+            }
+            _ => return,
+        }
+    };
+
+    let app = app.clone();
+    let root = root.to_string();
+    thread::spawn(move || {
+        thread::sleep(AUTO_RUN_DEBOUNCE);
+        let latest = app
+            .state::<ProblemsState>()
+            .projects
+            .lock()
+            .unwrap()
+            .get(&root)
+            .map(|p| p.change_generation)
+            .unwrap_or(0);
+        if latest == generation {
+            if let Err(e) = start_checkers(&app, &root, None) {
+                println!("[DEBUG] Failed to auto-run checkers: {}", e);
+            }
+        }
+    });
+}
+
+fn canonical_root(project_path: &str) -> Result<String, String> {
+    Path::new(project_path)
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to resolve project path: {}", e))
+}
+
+#[tauri::command]
+pub fn list_checkers(project_path: String) -> Result<Vec<CheckerConfig>, String> {
+    resolve_checkers(&project_path)
+}
+
+/// Start checkers in the background; returns the names of the checkers started
+#[tauri::command]
+pub fn run_checkers(app: AppHandle, project_path: String, checkers: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let root = canonical_root(&project_path)?;
+    start_checkers(&app, &root, checkers.as_deref())
+}
+
+/// Re-run checkers automatically whenever project files change
+#[tauri::command]
+pub fn set_problems_auto_run(state: State<'_, ProblemsState>, project_path: String, enabled: bool) -> Result<(), String> {
+    let root = canonical_root(&project_path)?;
+    let mut projects = state.projects.lock().unwrap();
+    projects.entry(root).or_default().auto_run = enabled;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_problems(state: State<'_, ProblemsState>, project_path: String) -> Result<ProblemsSummary, String> {
+    let root = canonical_root(&project_path)?;
+    let projects = state.projects.lock().unwrap();
+    let Some(project) = projects.get(&root) else {
+        return Ok(ProblemsSummary {
+            diagnostics: vec![],
+            files: HashMap::new(),
+            running: vec![],
+        });
+    };
+
+    let mut diagnostics: Vec<Diagnostic> = project
+        .results
+        .values()
+        .flat_map(|r| r.diagnostics.iter().cloned())
+        .collect();
+    diagnostics.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then(a.range.start_line.cmp(&b.range.start_line))
+            .then(a.range.start_column.cmp(&b.range.start_column))
+    });
+
+    let mut files: HashMap<String, FileProblemCounts> = HashMap::new();
+    for diagnostic in &diagnostics {
+        let counts = files.entry(diagnostic.path.clone()).or_default();
+        match diagnostic.severity.as_str() {
+            "error" => counts.errors += 1,
+            "warning" => counts.warnings += 1,
+            _ => {}
+        }
+    }
+
+    Ok(ProblemsSummary {
+        diagnostics,
+        files,
+        running: project.running.iter().cloned().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start_line: usize, start_column: usize, end_line: usize, end_column: usize) -> SymbolRange {
+        SymbolRange {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    #[test]
+    fn parses_cargo_messages_once_per_diagnostic() {
+        let message = r#"{"reason":"compiler-message","target":{"kind":["lib"]},"message":{"message":"unused variable: `x`","level":"warning","code":{"code":"unused_variables"},"spans":[{"file_name":"src/lib.rs","line_start":4,"line_end":4,"column_start":9,"column_end":10,"is_primary":true}]}}"#;
+        let output = [
+            r#"{"reason":"compiler-artifact","target":{"kind":["lib"]}}"#,
+            message,
+            // Reported again for the test target
+            message,
+            r#"{"reason":"compiler-message","message":{"message":"mismatched types","level":"error","code":{"code":"E0308"},"spans":[{"file_name":"/proj/src/main.rs","line_start":7,"line_end":8,"column_start":5,"column_end":2,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"2 warnings emitted","level":"warning","code":null,"spans":[]}}"#,
+            r#"{"reason":"build-finished","success":false}"#,
+        ]
+        .join("\n");
+
+        let diagnostics = parse_cargo("/proj", "cargo check", &output);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].path, "src/lib.rs");
+        assert_eq!(diagnostics[0].range, range(4, 8, 4, 9));
+        assert_eq!(diagnostics[0].severity, "warning");
+        assert_eq!(diagnostics[0].code.as_deref(), Some("unused_variables"));
+        assert_eq!(diagnostics[0].source, "cargo check");
+
+        assert_eq!(diagnostics[1].path, "src/main.rs");
+        assert_eq!(diagnostics[1].range, range(7, 4, 8, 1));
+        assert_eq!(diagnostics[1].severity, "error");
+        assert_eq!(diagnostics[1].message, "mismatched types");
+    }
+
+    #[test]
+    fn parses_tsc_lines_with_continuations() {
+        let output = "\
+src/app.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
+src/util.ts(3,10): error TS2345: Argument of type '{ a: string; }' is not assignable to parameter of type 'Options'.
+  Object literal may only specify known properties, and 'a' does not exist in type 'Options'.
+Found 2 errors in 2 files.
+";
+        let diagnostics = parse_tsc("/proj", "tsc", output);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].path, "src/app.ts");
+        assert_eq!(diagnostics[0].range, range(12, 4, 12, 4));
+        assert_eq!(diagnostics[0].severity, "error");
+        assert_eq!(diagnostics[0].code.as_deref(), Some("TS2322"));
+        assert_eq!(diagnostics[0].message, "Type 'string' is not assignable to type 'number'.");
+
+        assert_eq!(diagnostics[1].path, "src/util.ts");
+        assert!(diagnostics[1]
+            .message
+            .ends_with("\nObject literal may only specify known properties, and 'a' does not exist in type 'Options'."));
+    }
+
+    #[test]
+    fn parses_eslint_json_report() {
+        let output = r#"[
+            {
+                "filePath": "/proj/src/index.js",
+                "messages": [
+                    {"ruleId": "no-unused-vars", "severity": 2, "message": "'x' is defined but never used.", "line": 1, "column": 7, "endLine": 1, "endColumn": 8},
+                    {"ruleId": null, "severity": 1, "message": "Unused eslint-disable directive.", "line": 4, "column": 1}
+                ]
+            },
+            {"filePath": "/proj/src/clean.js", "messages": []}
+        ]"#;
+        let diagnostics = parse_eslint("/proj", "eslint", output);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].path, "src/index.js");
+        assert_eq!(diagnostics[0].range, range(1, 6, 1, 7));
+        assert_eq!(diagnostics[0].severity, "error");
+        assert_eq!(diagnostics[0].code.as_deref(), Some("no-unused-vars"));
+
+        assert_eq!(diagnostics[1].range, range(4, 0, 4, 0));
+        assert_eq!(diagnostics[1].severity, "warning");
+        assert_eq!(diagnostics[1].code, None);
+    }
+
+    #[test]
+    fn ignores_output_that_is_not_an_eslint_report() {
+        assert!(parse_eslint("/proj", "eslint", "Oops! Something went wrong!").is_empty());
+    }
+}
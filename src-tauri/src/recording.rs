@@ -0,0 +1,404 @@
+use crate::emulator::TerminalScreen;
+use crate::git::create_milhouse_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, LineWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+/// Recordings are kept with the project, next to its other Milhouse data
+const RECORDINGS_DIR: &str = ".milhouse/recordings";
+const RECORDING_EXTENSION: &str = "cast";
+/// Enough of the end of a recording to hold its last event
+const TAIL_BYTES: u64 = 64 * 1024;
+/// How often a paused playback checks whether it was stopped
+const PLAYBACK_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordingOptions {
+    /// Also record what is typed into the terminal
+    pub input: bool,
+    pub title: Option<String>,
+}
+
+/// First line of an asciicast v2 file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CastHeader {
+    pub version: u8,
+    pub width: u16,
+    pub height: u16,
+    /// Unix time in seconds
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Appends a terminal's output to an asciicast v2 file as it happens
+pub struct Recorder {
+    writer: LineWriter<File>,
+    started: Instant,
+    input: bool,
+    path: PathBuf,
+}
+
+impl Recorder {
+    /// Start a recording in the project's recordings directory. It opens with the terminal's
+    /// current screen, so recording can start at any point in a session.
+    pub fn create(
+        project_path: &str,
+        terminal_id: &str,
+        options: &RecordingOptions,
+        screen: &TerminalScreen,
+    ) -> Result<Self, String> {
+        let dir = recordings_dir(project_path);
+        create_milhouse_dir(project_path, &dir)?;
+        let short_id = &terminal_id[..terminal_id.len().min(8)];
+        let path = dir.join(format!("{}-{}.{}", now_millis(), short_id, RECORDING_EXTENSION));
+        let file = File::create(&path).map_err(|e| format!("Failed to create recording: {}", e))?;
+
+        let (rows, cols) = screen.size();
+        let title = options
+            .title
+            .clone()
+            .or_else(|| Some(screen.title().to_string()).filter(|t| !t.is_empty()));
+        let header = CastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: now_millis() / 1000,
+            title,
+        };
+        let mut recorder = Recorder {
+            writer: LineWriter::new(file),
+            started: Instant::now(),
+            input: options.input,
+            path,
+        };
+        let header = serde_json::to_string(&header).map_err(|e| format!("Failed to serialize recording header: {}", e))?;
+        writeln!(recorder.writer, "{}", header).map_err(|e| format!("Failed to write recording: {}", e))?;
+        recorder.event("o", &screen.replay());
+        println!("[DEBUG] Recording terminal {} to {}", terminal_id, recorder.path.display());
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn output(&mut self, data: &str) {
+        self.event("o", data);
+    }
+
+    /// Record keystrokes, if this recording includes input
+    pub fn input(&mut self, data: &str) {
+        if self.input {
+            self.event("i", data);
+        }
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        self.event("r", &format!("{}x{}", cols, rows));
+    }
+
+    fn event(&mut self, code: &str, data: &str) {
+        if data.is_empty() {
+            return;
+        }
+        // Microsecond precision, as asciinema writes it
+        let time = (self.started.elapsed().as_secs_f64() * 1e6).round() / 1e6;
+        if let Ok(line) = serde_json::to_string(&(time, code, data)) {
+            // A full disk should not take the terminal down; the recording just stops growing
+            let _ = writeln!(self.writer, "{}", line);
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct RecordingInfo {
+    /// File name, used to refer to the recording
+    pub name: String,
+    pub size: u64,
+    #[serde(flatten)]
+    pub header: CastHeader,
+    /// Seconds from the start to the last event
+    pub duration: f64,
+}
+
+/// Playbacks in progress, by id; clearing the flag stops one
+#[derive(Default)]
+pub struct RecordingState {
+    playbacks: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Playback {
+    pub id: String,
+    pub header: CastHeader,
+}
+
+/// Payload of `recording-event`: one event of a playback, at its (scaled) time
+#[derive(Serialize, Clone)]
+pub struct PlaybackEvent {
+    pub id: String,
+    pub time: f64,
+    /// "o" for output, "i" for input, "r" for a resize to "COLSxROWS"
+    pub code: String,
+    pub data: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PlaybackEnd {
+    pub id: String,
+    /// False when stopped early
+    pub completed: bool,
+}
+
+fn recordings_dir(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(RECORDINGS_DIR)
+}
+
+/// Path of a recording by file name, refusing names that would leave the recordings directory
+fn recording_path(project_path: &str, name: &str) -> Result<PathBuf, String> {
+    let path = Path::new(name);
+    let is_plain_name = path.file_name().is_some_and(|f| f == path.as_os_str());
+    let is_cast = path.extension().is_some_and(|e| e == RECORDING_EXTENSION);
+    if !is_plain_name || !is_cast {
+        return Err(format!("Invalid recording name: {}", name));
+    }
+    Ok(recordings_dir(project_path).join(name))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn read_header(reader: &mut impl BufRead) -> Result<CastHeader, String> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read recording: {}", e))?;
+    serde_json::from_str(&line).map_err(|e| format!("Failed to parse recording header: {}", e))
+}
+
+/// Time of the last complete event, read from the end of the file
+fn last_event_time(file: &mut File, size: u64) -> f64 {
+    let start = size.saturating_sub(TAIL_BYTES);
+    let mut tail = Vec::new();
+    if file.seek(SeekFrom::Start(start)).is_err() || file.read_to_end(&mut tail).is_err() {
+        return 0.0;
+    }
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<(f64, String, String)>(line).ok())
+        .map_or(0.0, |(time, _, _)| time)
+}
+
+fn recording_info(path: &Path) -> Result<RecordingInfo, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open recording: {}", e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| format!("Failed to read recording metadata: {}", e))?
+        .len();
+    let header = read_header(&mut BufReader::new(&mut file))?;
+    Ok(RecordingInfo {
+        name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        size,
+        header,
+        duration: last_event_time(&mut file, size),
+    })
+}
+
+/// Recordings in the project, newest first
+#[tauri::command]
+pub fn list_recordings(project_path: String) -> Result<Vec<RecordingInfo>, String> {
+    let dir = recordings_dir(&project_path);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read recordings directory: {}", e))?;
+    let mut recordings: Vec<RecordingInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == RECORDING_EXTENSION))
+        .filter_map(|path| match recording_info(&path) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                println!("[DEBUG] Skipping recording {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.header.timestamp.cmp(&a.header.timestamp).then_with(|| b.name.cmp(&a.name)));
+    Ok(recordings)
+}
+
+#[tauri::command]
+pub fn delete_recording(project_path: String, name: String) -> Result<(), String> {
+    let path = recording_path(&project_path, &name)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete recording: {}", e))?;
+    println!("[DEBUG] Deleted recording {}", path.display());
+    Ok(())
+}
+
+/// Play a recording back as `recording-event` events at its original pace, scaled by `speed`.
+/// Pauses longer than `idle_limit` seconds are shortened to it. Ends with `recording-end`.
+#[tauri::command]
+pub fn play_recording(
+    app: AppHandle,
+    state: State<'_, RecordingState>,
+    project_path: String,
+    name: String,
+    speed: Option<f64>,
+    idle_limit: Option<f64>,
+) -> Result<Playback, String> {
+    let path = recording_path(&project_path, &name)?;
+    let file = File::open(&path).map_err(|e| format!("Failed to open recording: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let header = read_header(&mut reader)?;
+    let speed = speed.filter(|s| *s > 0.0).unwrap_or(1.0);
+    let idle_limit = idle_limit.filter(|l| *l > 0.0).unwrap_or(f64::INFINITY);
+
+    let id = Uuid::new_v4().to_string();
+    let playing = Arc::new(AtomicBool::new(true));
+    state.playbacks.lock().unwrap().insert(id.clone(), playing.clone());
+
+    let playback_id = id.clone();
+    thread::spawn(move || {
+        let mut last_time = 0.0;
+        let mut elapsed = 0.0;
+        let mut completed = true;
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let Ok((time, code, data)) = serde_json::from_str::<(f64, String, String)>(&line) else {
+                continue;
+            };
+            let delay = (time - last_time).clamp(0.0, idle_limit) / speed;
+            last_time = time;
+            elapsed += delay;
+
+            let due = Instant::now() + Duration::from_secs_f64(delay);
+            while Instant::now() < due && playing.load(Ordering::Relaxed) {
+                thread::sleep(PLAYBACK_POLL.min(due.saturating_duration_since(Instant::now())));
+            }
+            if !playing.load(Ordering::Relaxed) {
+                completed = false;
+                break;
+            }
+            let _ = app.emit(
+                "recording-event",
+                PlaybackEvent {
+                    id: playback_id.clone(),
+                    time: elapsed,
+                    code,
+                    data,
+                },
+            );
+        }
+        app.state::<RecordingState>().playbacks.lock().unwrap().remove(&playback_id);
+        let _ = app.emit(
+            "recording-end",
+            PlaybackEnd {
+                id: playback_id,
+                completed,
+            },
+        );
+    });
+
+    Ok(Playback { id, header })
+}
+
+#[tauri::command]
+pub fn stop_playback(state: State<'_, RecordingState>, id: String) -> Result<(), String> {
+    let playing = state
+        .playbacks
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| format!("Playback not found: {}", id))?;
+    playing.store(false, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `content` to a fresh temporary file and measure it like `recording_info` does
+    fn duration_of(content: &str) -> f64 {
+        let path = std::env::temp_dir().join(format!("milhouse-test-{}.cast", Uuid::new_v4()));
+        fs::write(&path, content).unwrap();
+        let mut file = File::open(&path).unwrap();
+        let duration = last_event_time(&mut file, content.len() as u64);
+        let _ = fs::remove_file(&path);
+        duration
+    }
+
+    const HEADER: &str = "{\"version\":2,\"width\":80,\"height\":24,\"timestamp\":0}\n";
+
+    #[test]
+    fn last_event_time_of_header_only_recording_is_zero() {
+        assert_eq!(duration_of(HEADER), 0.0);
+        assert_eq!(duration_of(""), 0.0);
+    }
+
+    #[test]
+    fn last_event_time_reads_the_final_event() {
+        let content = format!("{}[0.5,\"o\",\"a\"]\n[1.25,\"r\",\"100x30\"]\n[2.75,\"o\",\"b\"]\n", HEADER);
+        assert_eq!(duration_of(&content), 2.75);
+    }
+
+    #[test]
+    fn last_event_time_skips_a_partly_written_event() {
+        let content = format!("{}[0.5,\"o\",\"a\"]\n[1.5,\"o\",\"b\"]\n[9.0,\"o\",\"unfinis", HEADER);
+        assert_eq!(duration_of(&content), 1.5);
+    }
+
+    #[test]
+    fn last_event_time_only_reads_the_tail_of_long_recordings() {
+        let mut content = HEADER.to_string();
+        let filler = "x".repeat(1000);
+        let mut time = 0.0;
+        while (content.len() as u64) < TAIL_BYTES * 3 {
+            time += 0.1;
+            content.push_str(&serde_json::to_string(&(time, "o", &filler)).unwrap());
+            content.push('\n');
+        }
+        assert_eq!(duration_of(&content), time);
+    }
+
+    #[test]
+    fn recording_path_accepts_plain_cast_names() {
+        let path = recording_path("/project", "123-abcd.cast").unwrap();
+        assert_eq!(path, Path::new("/project").join(RECORDINGS_DIR).join("123-abcd.cast"));
+    }
+
+    #[test]
+    fn recording_path_rejects_names_outside_the_recordings_directory() {
+        let names = [
+            "../escape.cast",
+            "nested/file.cast",
+            "/etc/passwd.cast",
+            "..",
+            "",
+            "notes.txt",
+            ".cast",
+            "file.cast/",
+        ];
+        for name in names {
+            assert!(recording_path("/project", name).is_err(), "{:?} should be rejected", name);
+        }
+    }
+}
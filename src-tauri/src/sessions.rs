@@ -0,0 +1,978 @@
+use crate::emulator::{ScreenSnapshot, TerminalScreen};
+use crate::recording::{Recorder, RecordingOptions};
+use crate::shell::ShellConfig;
+use crate::shell_integration::{emit_events, CommandHistory, CommandRecord, IntegrationEvent, ShellIntegration};
+use crate::terminal::{
+    checkpoint_before_session, open_shell, terminate, ExitInfo, KillReport, Readiness, Scrollback,
+    StartupOptions, TerminalExit, TerminalOutput, TerminalSnapshot, Utf8Decoder, EXIT_DRAIN_DELAY,
+};
+use portable_pty::{ChildKiller, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+// Stand-in so the client compiles elsewhere; connecting always fails off Unix
+#[cfg(not(unix))]
+type Stream = std::net::TcpStream;
+
+/// Command line flag that makes the app binary run as the session daemon
+pub const DAEMON_ARG: &str = "--session-daemon";
+
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(3);
+/// Longest a request waits on the daemon; killing a session alone can take several seconds
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Grace period before an idle daemon exits, so replies to in-flight requests still go out
+const IDLE_SHUTDOWN_DELAY: Duration = Duration::from_secs(1);
+/// Attached clients that stop reading for this long are disconnected
+const SUBSCRIBER_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Events queued for an attached client; one that falls further behind is dropped
+const SUBSCRIBER_QUEUE: usize = 1024;
+/// Reply to requests that arrive after an idle daemon has decided to exit
+const DAEMON_SHUTTING_DOWN: &str = "Session daemon is shutting down";
+const DAEMON_CLOSED: &str = "Session daemon closed the connection";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionInfo {
+    pub id: String,
+    pub cwd: Option<String>,
+    pub startup_command: Option<String>,
+    pub pid: Option<u32>,
+    pub created_at: u64,
+}
+
+/// One JSON line per request; `attach` turns the connection into an event stream
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    Create {
+        cwd: Option<String>,
+        startup_command: Option<String>,
+        #[serde(default)]
+        shell: ShellConfig,
+        #[serde(default)]
+        startup: StartupOptions,
+    },
+    List,
+    Attach {
+        id: String,
+    },
+    Write {
+        id: String,
+        data: String,
+    },
+    Resize {
+        id: String,
+        rows: u16,
+        cols: u16,
+    },
+    Kill {
+        id: String,
+    },
+    History {
+        id: String,
+    },
+    Screen {
+        id: String,
+        #[serde(default)]
+        styled: bool,
+    },
+    Record {
+        id: String,
+        project_path: String,
+        #[serde(default)]
+        options: RecordingOptions,
+    },
+    StopRecording {
+        id: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct Response {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sessions: Option<Vec<SessionInfo>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kill: Option<KillReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history: Option<CommandHistory>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    screen: Option<ScreenSnapshot>,
+    /// Path of a recording that was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recording: Option<String>,
+}
+
+impl Response {
+    fn from_result(result: Result<Response, String>) -> Self {
+        result.unwrap_or_else(|e| Response {
+            error: Some(e),
+            ..Default::default()
+        })
+    }
+
+    fn ok() -> Self {
+        Response {
+            ok: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Event {
+    Snapshot { data: String, offset: u64, created_at: u64 },
+    Output { data: String, offset: u64 },
+    Command { command: CommandRecord },
+    Cwd { cwd: String },
+    Exit { exit: TerminalExit },
+}
+
+/// Sessions being forwarded to the webview by this app instance
+#[derive(Default)]
+pub struct SessionState {
+    streaming: Arc<Mutex<HashSet<String>>>,
+    /// Per-session queues of keystrokes and resizes, sent in order by a forwarding thread
+    inputs: Arc<Mutex<HashMap<String, Sender<Request>>>>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Kept in its own directory so the socket is only reachable by the current user
+fn socket_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|h| h.join(".milhouse").join("sessions").join("daemon.sock"))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn send_line<T: Serialize>(stream: &mut Stream, value: &T) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.flush()
+}
+
+/// A message serialized once, to be queued for every attached client
+fn shared_line<T: Serialize>(value: &T) -> Option<Arc<str>> {
+    serde_json::to_string(value).ok().map(|line| Arc::from(line + "\n"))
+}
+
+struct Session {
+    info: SessionInfo,
+    master: Box<dyn MasterPty + Send>,
+    /// Locked on its own, so a shell that stops reading input only stalls writes to itself
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    exit: Arc<Mutex<Option<TerminalExit>>>,
+    output: Arc<Mutex<SessionOutput>>,
+}
+
+/// Scrollback and attached clients share a lock so a new client sees every byte exactly once
+#[derive(Default)]
+struct SessionOutput {
+    scrollback: Scrollback,
+    integration: ShellIntegration,
+    screen: TerminalScreen,
+    recorder: Option<Recorder>,
+    /// Queues drained by each attached client's writer thread, so a slow client never
+    /// blocks the PTY reader while it holds this lock
+    subscribers: Vec<SyncSender<Arc<str>>>,
+}
+
+impl SessionOutput {
+    fn broadcast(&mut self, event: &Event) {
+        let Some(line) = shared_line(event) else {
+            return;
+        };
+        self.subscribers.retain(|s| s.try_send(line.clone()).is_ok());
+    }
+}
+
+/// Requests being handled by the daemon, so it never exits underneath one
+struct Activity {
+    in_flight: usize,
+    /// An idle check found requests still running; the last of them repeats it
+    recheck_idle: bool,
+    /// Set once the daemon has decided to exit; later requests are refused
+    shutting_down: bool,
+}
+
+static ACTIVITY: Mutex<Activity> = Mutex::new(Activity {
+    in_flight: 0,
+    recheck_idle: false,
+    shutting_down: false,
+});
+
+/// Count a request as in flight, or refuse it once the daemon is shutting down
+fn begin_request() -> bool {
+    let mut activity = ACTIVITY.lock().unwrap();
+    if activity.shutting_down {
+        return false;
+    }
+    activity.in_flight += 1;
+    true
+}
+
+fn end_request(sessions: &Sessions) {
+    let recheck = {
+        let mut activity = ACTIVITY.lock().unwrap();
+        activity.in_flight -= 1;
+        activity.in_flight == 0 && std::mem::take(&mut activity.recheck_idle)
+    };
+    if recheck {
+        exit_if_idle(sessions);
+    }
+}
+
+/// Exit once no sessions are left and no request is being handled. Both locks are held until
+/// the process exits, so a `Create` cannot start between the check and the exit.
+fn exit_if_idle(sessions: &Sessions) {
+    let sessions = sessions.lock().unwrap();
+    let mut activity = ACTIVITY.lock().unwrap();
+    if !sessions.is_empty() {
+        return;
+    }
+    if activity.in_flight > 0 {
+        activity.recheck_idle = true;
+        return;
+    }
+    activity.shutting_down = true;
+    println!("[DEBUG] Session daemon: no sessions left, exiting");
+    if let Ok(path) = socket_path() {
+        let _ = std::fs::remove_file(path);
+    }
+    std::process::exit(0);
+}
+
+type Sessions = Arc<Mutex<HashMap<String, Session>>>;
+
+/// Entry point for `--session-daemon`: serve the session socket until the last session exits
+#[cfg(unix)]
+pub fn run_daemon() {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+
+    let path = match socket_path() {
+        Ok(path) => path,
+        Err(e) => {
+            println!("[DEBUG] Session daemon: {}", e);
+            return;
+        }
+    };
+    if Stream::connect(&path).is_ok() {
+        println!("[DEBUG] Session daemon already running");
+        return;
+    }
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir);
+        let _ = std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700));
+    }
+    // Left behind by a daemon that did not shut down cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("[DEBUG] Failed to bind session socket: {}", e);
+            return;
+        }
+    };
+    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    println!("[DEBUG] Session daemon listening on {}", path.display());
+
+    let sessions: Sessions = Arc::new(Mutex::new(HashMap::new()));
+    for stream in listener.incoming().flatten() {
+        let sessions = sessions.clone();
+        thread::spawn(move || handle_client(stream, sessions));
+    }
+}
+
+#[cfg(not(unix))]
+pub fn run_daemon() {
+    println!("[DEBUG] Persistent sessions are not supported on this platform");
+}
+
+fn handle_client(stream: Stream, sessions: Sessions) {
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let mut writer = stream;
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if !begin_request() {
+            let _ = send_line(&mut writer, &Response::from_result(Err(DAEMON_SHUTTING_DOWN.to_string())));
+            return;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Attach { id }) => {
+                subscribe(writer, &sessions, &id);
+                end_request(&sessions);
+                return;
+            }
+            Ok(request) => Response::from_result(handle_request(request, &sessions)),
+            Err(e) => Response::from_result(Err(format!("Invalid request: {}", e))),
+        };
+        let sent = send_line(&mut writer, &response);
+        end_request(&sessions);
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+fn handle_request(request: Request, sessions: &Sessions) -> Result<Response, String> {
+    match request {
+        Request::Create {
+            cwd,
+            startup_command,
+            shell,
+            startup,
+        } => {
+            let id = spawn_session(sessions, cwd, startup_command, &shell, startup)?;
+            Ok(Response {
+                id: Some(id),
+                ..Response::ok()
+            })
+        }
+        Request::List => {
+            let mut list: Vec<SessionInfo> = sessions.lock().unwrap().values().map(|s| s.info.clone()).collect();
+            list.sort_by_key(|s| s.created_at);
+            Ok(Response {
+                sessions: Some(list),
+                ..Response::ok()
+            })
+        }
+        Request::Write { id, data } => {
+            let (writer, output) = sessions
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|s| (s.writer.clone(), s.output.clone()))
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            let mut writer = writer.lock().unwrap();
+            writer
+                .write_all(data.as_bytes())
+                .and_then(|_| writer.flush())
+                .map_err(|e| format!("Failed to write to session: {}", e))?;
+            if let Some(recorder) = output.lock().unwrap().recorder.as_mut() {
+                recorder.input(&data);
+            }
+            Ok(Response::ok())
+        }
+        Request::Resize { id, rows, cols } => {
+            let sessions = sessions.lock().unwrap();
+            let session = sessions.get(&id).ok_or_else(|| format!("Session not found: {}", id))?;
+            session
+                .master
+                .resize(PtySize {
+                    rows,
+                    cols,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                })
+                .map_err(|e| format!("Failed to resize session: {}", e))?;
+            let mut output = session.output.lock().unwrap();
+            output.screen.resize(rows, cols);
+            if let Some(recorder) = output.recorder.as_mut() {
+                recorder.resize(rows, cols);
+            }
+            Ok(Response::ok())
+        }
+        Request::Kill { id } => {
+            let mut session = sessions
+                .lock()
+                .unwrap()
+                .remove(&id)
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            let report = terminate(&*session.master, session.info.pid, &mut *session.killer, &session.exit)?;
+            Ok(Response {
+                kill: Some(report),
+                ..Response::ok()
+            })
+        }
+        Request::History { id } => {
+            let output = sessions
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|s| s.output.clone())
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            let history = output.lock().unwrap().integration.history();
+            Ok(Response {
+                history: Some(history),
+                ..Response::ok()
+            })
+        }
+        Request::Screen { id, styled } => {
+            let output = sessions
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|s| s.output.clone())
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            let screen = output.lock().unwrap().screen.snapshot(styled);
+            Ok(Response {
+                screen: Some(screen),
+                ..Response::ok()
+            })
+        }
+        Request::Record {
+            id,
+            project_path,
+            options,
+        } => {
+            let output = sessions
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|s| s.output.clone())
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            let mut output = output.lock().unwrap();
+            let recorder = Recorder::create(&project_path, &id, &options, &output.screen)?;
+            let path = recorder.path().to_string_lossy().to_string();
+            output.recorder = Some(recorder);
+            Ok(Response {
+                recording: Some(path),
+                ..Response::ok()
+            })
+        }
+        Request::StopRecording { id } => {
+            let output = sessions
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|s| s.output.clone())
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            let recorder = output.lock().unwrap().recorder.take();
+            recorder
+                .map(|_| Response::ok())
+                .ok_or_else(|| format!("Session is not being recorded: {}", id))
+        }
+        Request::Attach { .. } => Err("Attach must be the only request on a connection".to_string()),
+    }
+}
+
+/// Reply with the scrollback, then keep the connection as a live output subscriber
+fn subscribe(mut stream: Stream, sessions: &Sessions, id: &str) {
+    let session = sessions.lock().unwrap().get(id).map(|s| (s.output.clone(), s.info.created_at));
+    let Some((output, created_at)) = session else {
+        let _ = send_line(&mut stream, &Response::from_result(Err(format!("Session not found: {}", id))));
+        return;
+    };
+
+    // The reply and snapshot are queued ahead of live output, taken under the same lock
+    let (sender, receiver) = mpsc::sync_channel(SUBSCRIBER_QUEUE);
+    {
+        let mut output = output.lock().unwrap();
+        let snapshot = Event::Snapshot {
+            data: output.scrollback.contents(),
+            offset: output.scrollback.end_offset(),
+            created_at,
+        };
+        for line in [shared_line(&Response::ok()), shared_line(&snapshot)].into_iter().flatten() {
+            let _ = sender.try_send(line);
+        }
+        output.subscribers.push(sender);
+    }
+    thread::spawn(move || forward_events(stream, receiver));
+}
+
+/// Write a client's queued events until it disconnects or the session drops it
+fn forward_events(mut stream: Stream, events: Receiver<Arc<str>>) {
+    let _ = stream.set_write_timeout(Some(SUBSCRIBER_WRITE_TIMEOUT));
+    for line in events {
+        if stream.write_all(line.as_bytes()).and_then(|_| stream.flush()).is_err() {
+            break;
+        }
+    }
+}
+
+fn spawn_session(
+    sessions: &Sessions,
+    cwd: Option<String>,
+    startup_command: Option<String>,
+    shell: &ShellConfig,
+    startup: StartupOptions,
+) -> Result<String, String> {
+    let typed_command = startup_command.clone().filter(|_| !startup.direct);
+    let readiness = match typed_command {
+        Some(_) => Some(Arc::new(Readiness::new(startup.ready_pattern.as_deref())?)),
+        None => None,
+    };
+    let program = startup_command.as_deref().filter(|_| startup.direct);
+    let (pair, mut child) = open_shell(cwd.as_deref(), program, shell)?;
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to clone reader: {}", e))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to take writer: {}", e))?;
+
+    let id = Uuid::new_v4().to_string();
+    let exit = Arc::new(Mutex::new(None));
+    let started = Instant::now();
+    let output = Arc::new(Mutex::new(SessionOutput::default()));
+    let info = SessionInfo {
+        id: id.clone(),
+        cwd,
+        startup_command: startup_command.clone(),
+        pid: child.process_id(),
+        created_at: now_millis(),
+    };
+    println!("[DEBUG] Session daemon: started session {} (pid {:?})", id, info.pid);
+    sessions.lock().unwrap().insert(
+        id.clone(),
+        Session {
+            info,
+            master: pair.master,
+            writer: Arc::new(Mutex::new(writer)),
+            killer: child.clone_killer(),
+            exit: exit.clone(),
+            output: output.clone(),
+        },
+    );
+
+    let reader_output = output.clone();
+    let readiness_for_reader = readiness.clone();
+    thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        let mut decoder = Utf8Decoder::default();
+        loop {
+            let (data, eof) = match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => (decoder.finish(), true),
+                Ok(n) => (decoder.decode(&buffer[..n]), false),
+            };
+            if !data.is_empty() {
+                if let Some(ref readiness) = readiness_for_reader {
+                    readiness.observe(&data);
+                }
+                let mut output = reader_output.lock().unwrap();
+                let events = output.integration.feed(&data);
+                output.screen.process(&data);
+                if let Some(recorder) = output.recorder.as_mut() {
+                    recorder.output(&data);
+                }
+                let offset = output.scrollback.push(&data);
+                output.broadcast(&Event::Output { data, offset });
+                for event in events {
+                    output.broadcast(&match event {
+                        IntegrationEvent::Command(command) => Event::Command { command },
+                        IntegrationEvent::Cwd(cwd) => Event::Cwd { cwd },
+                    });
+                }
+            }
+            if eof {
+                break;
+            }
+        }
+    });
+
+    let sessions_for_exit = sessions.clone();
+    let id_for_exit = id.clone();
+    thread::spawn(move || {
+        let status = ExitInfo::from_wait(child.wait());
+        thread::sleep(EXIT_DRAIN_DELAY);
+        println!("[DEBUG] Session daemon: session {} exited", id_for_exit);
+        sessions_for_exit.lock().unwrap().remove(&id_for_exit);
+        {
+            let mut output = output.lock().unwrap();
+            let report = TerminalExit::new(id_for_exit, status, started, &output.scrollback);
+            *exit.lock().unwrap() = Some(report.clone());
+            output.broadcast(&Event::Exit { exit: report });
+            output.subscribers.clear();
+        }
+        // Nothing left to keep alive; the next detached terminal starts a fresh daemon
+        thread::sleep(IDLE_SHUTDOWN_DELAY);
+        exit_if_idle(&sessions_for_exit);
+    });
+
+    if let (Some(cmd_to_run), Some(readiness)) = (typed_command, readiness) {
+        let sessions = sessions.clone();
+        let id = id.clone();
+        let timeout = startup.ready_timeout();
+        thread::spawn(move || {
+            if readiness.wait(timeout).is_none() {
+                println!("[DEBUG] Session daemon: shell not ready after {:?}, sending anyway", timeout);
+            }
+            let writer = sessions.lock().unwrap().get(&id).map(|s| s.writer.clone());
+            if let Some(writer) = writer {
+                let cmd_with_newline = format!("{}\n", cmd_to_run);
+                let mut writer = writer.lock().unwrap();
+                let _ = writer.write_all(cmd_with_newline.as_bytes());
+                let _ = writer.flush();
+            }
+        });
+    }
+
+    Ok(id)
+}
+
+#[cfg(unix)]
+fn connect() -> Result<Stream, String> {
+    Stream::connect(socket_path()?).map_err(|e| format!("Session daemon is not running: {}", e))
+}
+
+#[cfg(not(unix))]
+fn connect() -> Result<Stream, String> {
+    Err("Persistent sessions are not supported on this platform".to_string())
+}
+
+/// Start the daemon from this binary unless one is already listening
+fn ensure_daemon() -> Result<(), String> {
+    if connect().is_ok() {
+        return Ok(());
+    }
+    if cfg!(not(unix)) {
+        return connect().map(|_| ());
+    }
+
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate app binary: {}", e))?;
+    let mut cmd = Command::new(exe);
+    cmd.arg(DAEMON_ARG)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Own process group, so the daemon is not hung up along with the app
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start session daemon: {}", e))?;
+    println!("[DEBUG] Started session daemon (pid {})", child.id());
+    // Reap the daemon if it exits while the app is still running
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    let started = Instant::now();
+    loop {
+        match connect() {
+            Ok(_) => return Ok(()),
+            Err(e) if started.elapsed() >= DAEMON_START_TIMEOUT => return Err(e),
+            Err(_) => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+fn read_line<T: for<'de> Deserialize<'de>>(reader: &mut BufReader<Stream>) -> Result<T, String> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => Err(DAEMON_CLOSED.to_string()),
+        Ok(_) => serde_json::from_str(&line).map_err(|e| format!("Failed to parse session daemon message: {}", e)),
+        Err(e) => Err(format!("Failed to read from session daemon: {}", e)),
+    }
+}
+
+/// Connect with timeouts, so a stuck daemon fails requests instead of hanging them
+fn connect_with_timeouts() -> Result<Stream, String> {
+    let stream = connect()?;
+    let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(REQUEST_TIMEOUT));
+    Ok(stream)
+}
+
+/// Send one request on an open connection and read its response
+fn exchange(stream: &mut Stream, reader: &mut BufReader<Stream>, request: &Request) -> Result<Response, String> {
+    send_line(stream, request).map_err(|e| format!("Failed to send to session daemon: {}", e))?;
+    let response: Response = read_line(reader)?;
+    if response.ok {
+        Ok(response)
+    } else {
+        Err(response.error.unwrap_or_else(|| "Session daemon request failed".to_string()))
+    }
+}
+
+fn request(request: &Request) -> Result<Response, String> {
+    let mut stream = connect_with_timeouts()?;
+    let mut reader = BufReader::new(
+        stream
+            .try_clone()
+            .map_err(|e| format!("Failed to clone session daemon connection: {}", e))?,
+    );
+    exchange(&mut stream, &mut reader, request)
+}
+
+/// Send a session's queued keystrokes and resizes over one connection, in order, so the
+/// webview never waits on the daemon. The connection is reopened after a failure.
+fn forward_input(id: String, requests: Receiver<Request>) {
+    let mut connection: Option<(Stream, BufReader<Stream>)> = None;
+    for request in requests {
+        if connection.is_none() {
+            connection = connect_with_timeouts()
+                .and_then(|stream| {
+                    let reader = stream
+                        .try_clone()
+                        .map_err(|e| format!("Failed to clone session daemon connection: {}", e))?;
+                    Ok((stream, BufReader::new(reader)))
+                })
+                .map_err(|e| println!("[DEBUG] Failed to connect input for session {}: {}", id, e))
+                .ok();
+        }
+        let Some((stream, reader)) = connection.as_mut() else {
+            continue;
+        };
+        if let Err(e) = exchange(stream, reader, &request) {
+            println!("[DEBUG] Failed to send input to session {}: {}", id, e);
+            connection = None;
+        }
+    }
+}
+
+/// Queue a write or resize for a session this app is streaming
+fn queue_input(state: &SessionState, id: &str, request: Request) -> Result<(), String> {
+    if !state.streaming.lock().unwrap().contains(id) {
+        return Err(format!("Terminal not found: {}", id));
+    }
+    let mut inputs = state.inputs.lock().unwrap();
+    let sender = inputs.entry(id.to_string()).or_insert_with(|| {
+        let (sender, receiver) = mpsc::channel();
+        let id = id.to_string();
+        thread::spawn(move || forward_input(id, receiver));
+        sender
+    });
+    sender
+        .send(request)
+        .map_err(|_| format!("Input for session {} is closed", id))
+}
+
+/// Subscribe to a session, returning the stream positioned after its scrollback snapshot
+fn open_attach(id: &str) -> Result<(BufReader<Stream>, TerminalSnapshot, u64), String> {
+    let mut stream = connect_with_timeouts()?;
+    send_line(&mut stream, &Request::Attach { id: id.to_string() })
+        .map_err(|e| format!("Failed to send to session daemon: {}", e))?;
+    let mut reader = BufReader::new(stream);
+    let response: Response = read_line(&mut reader)?;
+    if !response.ok {
+        return Err(response.error.unwrap_or_else(|| format!("Session not found: {}", id)));
+    }
+    let snapshot = read_line(&mut reader)?;
+    // Live output can be quiet for any length of time
+    let _ = reader.get_ref().set_read_timeout(None);
+    match snapshot {
+        Event::Snapshot {
+            data,
+            offset,
+            created_at,
+        } => Ok((
+            reader,
+            TerminalSnapshot {
+                id: id.to_string(),
+                data,
+                offset,
+            },
+            created_at,
+        )),
+        _ => Err("Session daemon did not send a snapshot".to_string()),
+    }
+}
+
+/// Forward a session's output to the webview as `terminal-output` events, once per session
+fn start_stream(app: &AppHandle, state: &SessionState, id: String) -> Result<(), String> {
+    if !state.streaming.lock().unwrap().insert(id.clone()) {
+        return Ok(());
+    }
+    let (reader, _, created_at) = match open_attach(&id) {
+        Ok(attached) => attached,
+        Err(e) => {
+            state.streaming.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+    };
+
+    let app = app.clone();
+    let streaming = state.streaming.clone();
+    let inputs = state.inputs.clone();
+    let forget = move |id: &str| {
+        streaming.lock().unwrap().remove(id);
+        inputs.lock().unwrap().remove(id);
+    };
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match serde_json::from_str::<Event>(&line) {
+                Ok(Event::Output { data, offset }) => {
+                    let _ = app.emit(
+                        "terminal-output",
+                        TerminalOutput {
+                            id: id.clone(),
+                            data,
+                            offset,
+                        },
+                    );
+                }
+                Ok(Event::Command { command }) => emit_events(&app, &id, vec![IntegrationEvent::Command(command)]),
+                Ok(Event::Cwd { cwd }) => emit_events(&app, &id, vec![IntegrationEvent::Cwd(cwd)]),
+                Ok(Event::Exit { exit }) => {
+                    forget(&id);
+                    let _ = app.emit("terminal-exit", exit);
+                    return;
+                }
+                _ => {}
+            }
+        }
+        // The daemon went away without reporting how the session ended
+        forget(&id);
+        let _ = app.emit(
+            "terminal-exit",
+            TerminalExit {
+                id,
+                exit: ExitInfo::default(),
+                duration_ms: now_millis().saturating_sub(created_at),
+                last_lines: vec![],
+            },
+        );
+    });
+    Ok(())
+}
+
+/// Start a shell owned by the session daemon, so it survives the app closing
+pub fn create_session(
+    app: &AppHandle,
+    state: &SessionState,
+    cwd: Option<String>,
+    startup_command: Option<String>,
+    shell: ShellConfig,
+    startup: StartupOptions,
+    checkpoint: bool,
+) -> Result<String, String> {
+    if checkpoint {
+        checkpoint_before_session(app, cwd.as_deref());
+    }
+    let create = Request::Create {
+        cwd,
+        startup_command,
+        shell,
+        startup,
+    };
+    ensure_daemon()?;
+    let response = match request(&create) {
+        // An idle daemon can exit between the check above and the request; start a new one
+        Err(e) if e == DAEMON_SHUTTING_DOWN || e == DAEMON_CLOSED => {
+            ensure_daemon()?;
+            request(&create)
+        }
+        result => result,
+    }?;
+    let id = response
+        .id
+        .ok_or_else(|| "Session daemon did not return an id".to_string())?;
+    start_stream(app, state, id.clone())?;
+    Ok(id)
+}
+
+/// Queue input for a session; failures are logged by the forwarding thread
+pub fn write_session(state: &SessionState, id: &str, data: &str) -> Result<(), String> {
+    queue_input(
+        state,
+        id,
+        Request::Write {
+            id: id.to_string(),
+            data: data.to_string(),
+        },
+    )
+}
+
+pub fn resize_session(state: &SessionState, id: &str, rows: u16, cols: u16) -> Result<(), String> {
+    queue_input(
+        state,
+        id,
+        Request::Resize {
+            id: id.to_string(),
+            rows,
+            cols,
+        },
+    )
+}
+
+pub fn kill_session(id: &str) -> Result<KillReport, String> {
+    request(&Request::Kill { id: id.to_string() })?
+        .kill
+        .ok_or_else(|| "Session daemon did not report an exit status".to_string())
+}
+
+pub fn session_history(id: &str) -> Result<CommandHistory, String> {
+    request(&Request::History { id: id.to_string() })?
+        .history
+        .ok_or_else(|| "Session daemon did not return a history".to_string())
+}
+
+pub fn session_screen(id: &str, styled: bool) -> Result<ScreenSnapshot, String> {
+    request(&Request::Screen {
+        id: id.to_string(),
+        styled,
+    })?
+    .screen
+    .ok_or_else(|| "Session daemon did not return a screen".to_string())
+}
+
+pub fn record_session(id: &str, project_path: &str, options: RecordingOptions) -> Result<String, String> {
+    request(&Request::Record {
+        id: id.to_string(),
+        project_path: project_path.to_string(),
+        options,
+    })?
+    .recording
+    .ok_or_else(|| "Session daemon did not return a recording path".to_string())
+}
+
+pub fn stop_session_recording(id: &str) -> Result<(), String> {
+    request(&Request::StopRecording { id: id.to_string() }).map(|_| ())
+}
+
+pub fn attach_session(id: &str) -> Result<TerminalSnapshot, String> {
+    open_attach(id).map(|(_, snapshot, _)| snapshot)
+}
+
+/// Whether `id` is a session running in the daemon
+pub fn is_session(id: &str) -> bool {
+    list_sessions(None).is_ok_and(|sessions| sessions.iter().any(|s| s.id == id))
+}
+
+/// Sessions still running in the daemon, optionally only those started in `project_path`
+#[tauri::command]
+pub fn list_sessions(project_path: Option<String>) -> Result<Vec<SessionInfo>, String> {
+    // No daemon means nothing survived
+    if connect().is_err() {
+        return Ok(vec![]);
+    }
+    let sessions = request(&Request::List)?.sessions.unwrap_or_default();
+    Ok(sessions
+        .into_iter()
+        .filter(|s| project_path.is_none() || s.cwd == project_path)
+        .collect())
+}
+
+/// Resume streaming a surviving session's output; follow with `attach_terminal` for its scrollback
+#[tauri::command]
+pub fn reattach_session(app: AppHandle, state: State<'_, SessionState>, id: String) -> Result<(), String> {
+    println!("[DEBUG] Reattaching session {}", id);
+    start_stream(&app, &state, id)
+}
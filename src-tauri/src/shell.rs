@@ -0,0 +1,152 @@
+use portable_pty::CommandBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+/// App settings store, shared with the frontend
+const SETTINGS_STORE: &str = "settings.json";
+const PROFILES_KEY: &str = "terminalProfiles";
+
+/// Program, arguments and environment for a terminal; by default the user's login shell
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ShellConfig {
+    /// Program to run instead of the user's shell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Inherited variables to remove
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unset_env: Vec<String>,
+    /// Start as a login shell (`-l`). Defaults to true for the user's shell on Unix and false
+    /// otherwise; other programs get their arguments exactly as given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login: Option<bool>,
+}
+
+/// A named shell setup from app settings, e.g. "zsh", "bash --norc" or "nix develop"
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TerminalProfile {
+    pub name: String,
+    #[serde(flatten)]
+    pub shell: ShellConfig,
+}
+
+impl ShellConfig {
+    /// This configuration with `overrides` applied on top
+    pub fn merge(&self, overrides: &ShellConfig) -> ShellConfig {
+        // A new program or argument list replaces the command line as a whole
+        let replaces_command = overrides.program.is_some() || !overrides.args.is_empty();
+        let mut env = self.env.clone();
+        env.extend(overrides.env.clone());
+        ShellConfig {
+            program: overrides.program.clone().or_else(|| self.program.clone()),
+            args: if replaces_command {
+                overrides.args.clone()
+            } else {
+                self.args.clone()
+            },
+            env,
+            unset_env: self.unset_env.iter().chain(&overrides.unset_env).cloned().collect(),
+            login: overrides.login.or(self.login),
+        }
+    }
+
+    /// The PTY program and its arguments
+    pub fn command(&self) -> CommandBuilder {
+        // Windows shells have no `-l`
+        let login = self.login.unwrap_or(cfg!(unix) && self.program.is_none());
+        // portable-pty runs its default program as a login shell, but it takes no arguments
+        if self.program.is_none() && login && self.args.is_empty() {
+            return CommandBuilder::new_default_prog();
+        }
+
+        let program = self
+            .program
+            .clone()
+            .unwrap_or_else(|| CommandBuilder::new_default_prog().get_shell());
+        let mut cmd = CommandBuilder::new(program);
+        if login {
+            cmd.arg("-l");
+        }
+        cmd.args(&self.args);
+        cmd
+    }
+
+    /// Apply `env` and `unset_env` on top of the inherited environment
+    pub fn apply_env(&self, cmd: &mut CommandBuilder) {
+        for key in &self.unset_env {
+            cmd.env_remove(key);
+        }
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+    }
+}
+
+fn load_profiles(app: &AppHandle) -> Result<Vec<TerminalProfile>, String> {
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    match store.get(PROFILES_KEY) {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("Failed to parse terminal profiles: {}", e)),
+        None => Ok(vec![]),
+    }
+}
+
+/// The shell for a new terminal: the named profile, if any, with `overrides` on top
+pub fn resolve_shell(
+    app: &AppHandle,
+    profile: Option<&str>,
+    overrides: Option<&ShellConfig>,
+) -> Result<ShellConfig, String> {
+    let base = match profile {
+        Some(name) => load_profiles(app)?
+            .into_iter()
+            .find(|p| p.name == name)
+            .map(|p| p.shell)
+            .ok_or_else(|| format!("Terminal profile not found: {}", name))?,
+        None => ShellConfig::default(),
+    };
+    Ok(match overrides {
+        Some(overrides) => base.merge(overrides),
+        None => base,
+    })
+}
+
+#[tauri::command]
+pub fn list_terminal_profiles(app: AppHandle) -> Result<Vec<TerminalProfile>, String> {
+    load_profiles(&app)
+}
+
+#[tauri::command]
+pub fn save_terminal_profiles(app: AppHandle, mut profiles: Vec<TerminalProfile>) -> Result<(), String> {
+    for profile in &mut profiles {
+        profile.name = profile.name.trim().to_string();
+    }
+    let mut names = HashSet::new();
+    for profile in &profiles {
+        let name = profile.name.as_str();
+        if name.is_empty() {
+            return Err("Terminal profile names cannot be empty".to_string());
+        }
+        if !names.insert(name) {
+            return Err(format!("Duplicate terminal profile: {}", name));
+        }
+    }
+
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let value = serde_json::to_value(&profiles).map_err(|e| format!("Failed to serialize terminal profiles: {}", e))?;
+    store.set(PROFILES_KEY, value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+    println!("[DEBUG] Saved {} terminal profiles", profiles.len());
+    Ok(())
+}
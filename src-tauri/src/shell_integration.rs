@@ -0,0 +1,448 @@
+use crate::terminal::strip_escapes;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// Finished commands kept per terminal
+const MAX_HISTORY: usize = 500;
+/// An escape sequence still unterminated after this many bytes is dropped rather than buffered
+const MAX_PENDING_BYTES: usize = 4096;
+/// Echoed command line text kept while the user types
+const MAX_INPUT_BYTES: usize = 4096;
+
+/// One command run at a shell prompt. `duration_ms` is set once it has finished.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommandRecord {
+    /// As reported by the shell, or else as echoed at the prompt
+    pub command: Option<String>,
+    pub cwd: Option<String>,
+    pub started_at: u64,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Payload of `terminal-command`, emitted when a command starts and again when it finishes
+#[derive(Serialize, Clone)]
+pub struct CommandEvent {
+    pub id: String,
+    #[serde(flatten)]
+    pub command: CommandRecord,
+}
+
+/// Payload of `terminal-cwd`, emitted when the shell reports a new working directory
+#[derive(Serialize, Clone)]
+pub struct CwdEvent {
+    pub id: String,
+    pub cwd: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CommandHistory {
+    pub cwd: Option<String>,
+    /// The command running now, if any
+    pub current: Option<CommandRecord>,
+    /// Finished commands, oldest first
+    pub commands: Vec<CommandRecord>,
+}
+
+pub enum IntegrationEvent {
+    Command(CommandRecord),
+    Cwd(String),
+}
+
+/// Follows the OSC 133 (prompt/command marks), OSC 7 (cwd) and VS Code OSC 633 sequences a shell
+/// emits when its integration script is loaded. Output from shells without one passes through
+/// and leaves the history empty.
+#[derive(Default)]
+pub struct ShellIntegration {
+    /// Start of an escape sequence split across reads
+    pending: String,
+    /// Echoed text since the end of the prompt, while the user is typing a command
+    input: Option<String>,
+    /// Command line reported with OSC 633;E
+    reported_command: Option<String>,
+    cwd: Option<String>,
+    current: Option<(CommandRecord, Instant)>,
+    history: VecDeque<CommandRecord>,
+}
+
+impl ShellIntegration {
+    /// Scan a chunk of output, returning commands that started or finished and cwd changes
+    pub fn feed(&mut self, data: &str) -> Vec<IntegrationEvent> {
+        let mut text = std::mem::take(&mut self.pending);
+        text.push_str(data);
+        let mut events = Vec::new();
+        let mut rest = text.as_str();
+        loop {
+            let Some(start) = rest.find("\x1b]") else {
+                // A trailing ESC may begin the next read's sequence
+                let plain = rest.strip_suffix('\x1b').unwrap_or(rest);
+                self.observe_text(plain);
+                self.pending = rest[plain.len()..].to_string();
+                break;
+            };
+            self.observe_text(&rest[..start]);
+            let body = &rest[start + 2..];
+            match osc_end(body) {
+                Some((payload_end, next)) => {
+                    self.handle_osc(&body[..payload_end], &mut events);
+                    rest = &body[next..];
+                }
+                None => {
+                    if body.len() <= MAX_PENDING_BYTES {
+                        self.pending = rest[start..].to_string();
+                    }
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    pub fn history(&self) -> CommandHistory {
+        CommandHistory {
+            cwd: self.cwd.clone(),
+            current: self.current.as_ref().map(|(record, _)| record.clone()),
+            commands: self.history.iter().cloned().collect(),
+        }
+    }
+
+    pub fn cwd(&self) -> Option<String> {
+        self.cwd.clone()
+    }
+
+    fn observe_text(&mut self, text: &str) {
+        if let Some(input) = self.input.as_mut() {
+            if input.len() < MAX_INPUT_BYTES {
+                input.push_str(text);
+            }
+        }
+    }
+
+    fn handle_osc(&mut self, payload: &str, events: &mut Vec<IntegrationEvent>) {
+        let (code, args) = payload.split_once(';').unwrap_or((payload, ""));
+        match code {
+            "133" | "633" => {
+                let mut parts = args.splitn(2, ';');
+                let mark = parts.next().unwrap_or("");
+                let param = parts.next();
+                match mark {
+                    // A new prompt also ends a command whose shell did not report it
+                    "A" => {
+                        self.finish(None, events);
+                        self.input = None;
+                        self.reported_command = None;
+                    }
+                    "B" => self.input = Some(String::new()),
+                    "C" => self.start(events),
+                    "D" => {
+                        let exit_code = param.and_then(|p| p.split(';').next()).and_then(|c| c.parse().ok());
+                        self.finish(exit_code, events);
+                    }
+                    "E" if code == "633" => {
+                        let command = param.unwrap_or("").split(';').next().unwrap_or("");
+                        self.reported_command = Some(unescape_633(command));
+                    }
+                    "P" if code == "633" => {
+                        if let Some(cwd) = param.and_then(|p| p.strip_prefix("Cwd=")) {
+                            self.set_cwd(unescape_633(cwd), events);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "7" => {
+                if let Some(path) = file_url_path(args) {
+                    self.set_cwd(path, events);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, events: &mut Vec<IntegrationEvent>) {
+        self.finish(None, events);
+        let command = self
+            .reported_command
+            .take()
+            .filter(|c| !c.trim().is_empty())
+            .or_else(|| self.input.take().and_then(|raw| echoed_command(&raw)));
+        self.input = None;
+        let record = CommandRecord {
+            command,
+            cwd: self.cwd.clone(),
+            started_at: now_millis(),
+            exit_code: None,
+            duration_ms: None,
+        };
+        events.push(IntegrationEvent::Command(record.clone()));
+        self.current = Some((record, Instant::now()));
+    }
+
+    fn finish(&mut self, exit_code: Option<i32>, events: &mut Vec<IntegrationEvent>) {
+        let Some((mut record, started)) = self.current.take() else {
+            return;
+        };
+        record.exit_code = exit_code;
+        record.duration_ms = Some(started.elapsed().as_millis() as u64);
+        if self.history.len() >= MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(record.clone());
+        events.push(IntegrationEvent::Command(record));
+    }
+
+    fn set_cwd(&mut self, cwd: String, events: &mut Vec<IntegrationEvent>) {
+        if cwd.is_empty() || self.cwd.as_deref() == Some(cwd.as_str()) {
+            return;
+        }
+        self.cwd = Some(cwd.clone());
+        events.push(IntegrationEvent::Cwd(cwd));
+    }
+}
+
+/// Emit a terminal's integration events to the webview
+pub fn emit_events(app: &AppHandle, id: &str, events: Vec<IntegrationEvent>) {
+    for event in events {
+        let _ = match event {
+            IntegrationEvent::Command(command) => app.emit(
+                "terminal-command",
+                CommandEvent {
+                    id: id.to_string(),
+                    command,
+                },
+            ),
+            IntegrationEvent::Cwd(cwd) => app.emit(
+                "terminal-cwd",
+                CwdEvent {
+                    id: id.to_string(),
+                    cwd,
+                },
+            ),
+        };
+    }
+}
+
+/// Where an OSC payload ends and where the text after its terminator (BEL or ST) begins;
+/// None while the terminator has not arrived yet
+fn osc_end(body: &str) -> Option<(usize, usize)> {
+    for (i, c) in body.char_indices() {
+        match c {
+            '\x07' => return Some((i, i + 1)),
+            '\x1b' => {
+                return match body[i + 1..].chars().next() {
+                    Some('\\') => Some((i, i + 2)),
+                    // Cancelled by the next escape sequence
+                    Some(_) => Some((i, i)),
+                    None => None,
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The command line as echoed at the prompt, with escapes removed and backspaces applied
+fn echoed_command(raw: &str) -> Option<String> {
+    let mut line = String::new();
+    for (i, segment) in raw.split('\x08').enumerate() {
+        if i > 0 {
+            line.pop();
+        }
+        line.push_str(&strip_escapes(segment));
+    }
+    // Enter echoes a newline; line editors redraw from a carriage return
+    let first = line.split('\n').next().unwrap_or("");
+    let command = first.rsplit('\r').find(|s| !s.trim().is_empty())?.trim();
+    Some(command.to_string())
+}
+
+/// Path of an OSC 7 `file://host/path` URL
+fn file_url_path(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    Some(percent_decode(path))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = text.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// OSC 633 values escape `\` as `\\` and other bytes as `\xAB`
+fn unescape_633(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if bytes.get(i + 1) == Some(&b'\\') {
+                decoded.push(b'\\');
+                i += 2;
+                continue;
+            }
+            let hex = text.get(i + 2..i + 4).and_then(|h| u8::from_str_radix(h, 16).ok());
+            if let (Some(b'x'), Some(byte)) = (bytes.get(i + 1), hex) {
+                decoded.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cwds(events: &[IntegrationEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                IntegrationEvent::Cwd(cwd) => Some(cwd.clone()),
+                IntegrationEvent::Command(_) => None,
+            })
+            .collect()
+    }
+
+    fn commands(events: &[IntegrationEvent]) -> Vec<CommandRecord> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                IntegrationEvent::Command(record) => Some(record.clone()),
+                IntegrationEvent::Cwd(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accepts_bel_and_st_terminators() {
+        let mut integration = ShellIntegration::default();
+        let events = integration.feed("\x1b]7;file://host/tmp/a\x07\x1b]7;file://host/tmp/b\x1b\\");
+        assert_eq!(cwds(&events), vec!["/tmp/a", "/tmp/b"]);
+    }
+
+    #[test]
+    fn joins_a_sequence_split_across_reads() {
+        let mut integration = ShellIntegration::default();
+        assert!(integration.feed("prompt \x1b]7;file://host/ho").is_empty());
+        assert!(integration.feed("me/user\x1b").is_empty());
+        let events = integration.feed("\\ done");
+        assert_eq!(cwds(&events), vec!["/home/user"]);
+    }
+
+    #[test]
+    fn holds_a_trailing_escape_for_the_next_read() {
+        let mut integration = ShellIntegration::default();
+        assert!(integration.feed("output\x1b").is_empty());
+        let events = integration.feed("]7;file:///srv\x07");
+        assert_eq!(cwds(&events), vec!["/srv"]);
+    }
+
+    #[test]
+    fn next_escape_ends_an_unterminated_sequence() {
+        let mut integration = ShellIntegration::default();
+        let events = integration.feed("\x1b]7;file:///ignored\x1b]7;file:///kept\x07");
+        assert_eq!(cwds(&events), vec!["/ignored", "/kept"]);
+        assert_eq!(integration.cwd().as_deref(), Some("/kept"));
+    }
+
+    #[test]
+    fn drops_an_oversized_unterminated_sequence() {
+        let mut integration = ShellIntegration::default();
+        let long = format!("\x1b]7;file:///{}", "x".repeat(MAX_PENDING_BYTES + 1));
+        assert!(integration.feed(&long).is_empty());
+        let events = integration.feed("\x1b]7;file:///after\x07");
+        assert_eq!(cwds(&events), vec!["/after"]);
+    }
+
+    #[test]
+    fn records_a_command_with_its_exit_code() {
+        let mut integration = ShellIntegration::default();
+        integration.feed("\x1b]133;A\x07$ \x1b]133;B\x07");
+        integration.feed("make test\r\n");
+        let started = commands(&integration.feed("\x1b]133;C\x07"));
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].command.as_deref(), Some("make test"));
+        assert_eq!(started[0].exit_code, None);
+
+        let finished = commands(&integration.feed("ok\r\n\x1b]133;D;2\x07"));
+        assert_eq!(finished[0].exit_code, Some(2));
+        assert!(finished[0].duration_ms.is_some());
+        let history = integration.history();
+        assert!(history.current.is_none());
+        assert_eq!(history.commands.len(), 1);
+    }
+
+    #[test]
+    fn echoed_command_applies_backspaces() {
+        let mut integration = ShellIntegration::default();
+        integration.feed("\x1b]133;B\x07lz\x08s -la\r\n");
+        let started = commands(&integration.feed("\x1b]133;C\x07"));
+        assert_eq!(started[0].command.as_deref(), Some("ls -la"));
+    }
+
+    #[test]
+    fn prefers_the_command_reported_with_633_e() {
+        let mut integration = ShellIntegration::default();
+        integration.feed("\x1b]633;B\x07echo a\r\n");
+        integration.feed("\x1b]633;E;echo \\x3b \\\\n;nonce\x07");
+        let started = commands(&integration.feed("\x1b]633;C\x07"));
+        assert_eq!(started[0].command.as_deref(), Some("echo ; \\n"));
+    }
+
+    #[test]
+    fn reads_cwd_from_633_p() {
+        let mut integration = ShellIntegration::default();
+        let events = integration.feed("\x1b]633;P;Cwd=/tmp/with\\x3bsemicolon\x07");
+        assert_eq!(cwds(&events), vec!["/tmp/with;semicolon"]);
+        // Reporting the same directory again is not a change
+        assert!(integration.feed("\x1b]633;P;Cwd=/tmp/with\\x3bsemicolon\x07").is_empty());
+    }
+
+    #[test]
+    fn unescapes_633_values() {
+        assert_eq!(unescape_633(r"a\\b"), r"a\b");
+        assert_eq!(unescape_633(r"\x41\x3b"), "A;");
+        // Malformed escapes are kept as written
+        assert_eq!(unescape_633(r"\xZZ\x4"), r"\xZZ\x4");
+        assert_eq!(unescape_633(r"end\"), r"end\");
+    }
+
+    #[test]
+    fn decodes_file_urls() {
+        assert_eq!(file_url_path("file://host/a%20b/%E2%82%AC").as_deref(), Some("/a b/€"));
+        assert_eq!(file_url_path("file:///plain").as_deref(), Some("/plain"));
+        // Stray percent signs are left alone
+        assert_eq!(file_url_path("file:///100%/%zz%4").as_deref(), Some("/100%/%zz%4"));
+        assert_eq!(file_url_path("file://host-only"), None);
+        assert_eq!(file_url_path("http://host/path"), None);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::git::{is_git_repo, run_git};
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Files larger than this are counted by size but not read
+const MAX_COUNTED_BYTES: u64 = 10 * 1024 * 1024;
+
+const DEFAULT_TOP_FILES: usize = 10;
+const DEFAULT_CHURN_DAYS: u32 = 30;
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FileSize {
+    /// Project-relative path with forward slashes
+    pub path: String,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ChurnedFile {
+    pub path: String,
+    /// Commits touching the file in the churn window
+    pub commits: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CodebaseStats {
+    /// Sorted by lines of code, largest first
+    pub languages: Vec<LanguageStats>,
+    /// Totals over recognised languages
+    pub total: LanguageStats,
+    /// Every file walked, recognised or not
+    pub files: usize,
+    pub bytes: u64,
+    pub largest_files: Vec<FileSize>,
+    /// Empty when the project is not a git repository
+    pub churn: Vec<ChurnedFile>,
+    pub churn_days: u32,
+    pub duration_ms: u64,
+}
+
+struct Syntax {
+    name: &'static str,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+/// Line comment prefixes and block comment delimiters
+type Comments = (&'static [&'static str], Option<(&'static str, &'static str)>);
+
+const C_STYLE: Comments = (&["//"], Some(("/*", "*/")));
+const HASH: Comments = (&["#"], None);
+const CSS: Comments = (&[], Some(("/*", "*/")));
+const MARKUP: Comments = (&[], Some(("<!--", "-->")));
+const SQL: Comments = (&["--"], Some(("/*", "*/")));
+const NONE: Comments = (&[], None);
+
+fn syntax_for(path: &Path) -> Option<Syntax> {
+    let file_name = path.file_name()?.to_str()?;
+    let (name, (line_comments, block_comment)) = match file_name {
+        "Makefile" | "makefile" | "GNUmakefile" => ("Makefile", HASH),
+        "Dockerfile" => ("Dockerfile", HASH),
+        "justfile" | "Justfile" => ("Just", HASH),
+        _ => match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "rs" => ("Rust", C_STYLE),
+            "ts" | "tsx" | "mts" | "cts" => ("TypeScript", C_STYLE),
+            "js" | "jsx" | "mjs" | "cjs" => ("JavaScript", C_STYLE),
+            "py" | "pyi" => ("Python", HASH),
+            "go" => ("Go", C_STYLE),
+            "c" | "h" => ("C", C_STYLE),
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" => ("C++", C_STYLE),
+            "cs" => ("C#", C_STYLE),
+            "java" => ("Java", C_STYLE),
+            "kt" | "kts" => ("Kotlin", C_STYLE),
+            "swift" => ("Swift", C_STYLE),
+            "rb" => ("Ruby", HASH),
+            "php" => ("PHP", C_STYLE),
+            "sh" | "bash" | "zsh" => ("Shell", HASH),
+            "css" => ("CSS", CSS),
+            "scss" | "less" => ("SCSS", C_STYLE),
+            "html" | "htm" => ("HTML", MARKUP),
+            "vue" => ("Vue", MARKUP),
+            "sql" => ("SQL", SQL),
+            "toml" => ("TOML", HASH),
+            "yaml" | "yml" => ("YAML", HASH),
+            "json" => ("JSON", NONE),
+            "md" | "markdown" => ("Markdown", MARKUP),
+            _ => return None,
+        },
+    };
+    Some(Syntax {
+        name,
+        line_comments,
+        block_comment,
+    })
+}
+
+/// Classify each line as code, comment or blank. Comment markers inside strings are not
+/// recognised; a line with any code on it counts as code.
+fn count_lines(source: &str, syntax: &Syntax) -> (usize, usize, usize) {
+    let (mut code, mut comments, mut blanks) = (0, 0, 0);
+    let mut in_block = false;
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            blanks += 1;
+            continue;
+        }
+
+        if in_block {
+            comments += 1;
+            if let Some((_, end)) = syntax.block_comment {
+                if let Some(index) = trimmed.find(end) {
+                    in_block = false;
+                    // Code after the comment closes makes this a code line
+                    if !trimmed[index + end.len()..].trim().is_empty() {
+                        comments -= 1;
+                        code += 1;
+                    }
+                }
+            }
+            continue;
+        }
+
+        if syntax.line_comments.iter().any(|c| trimmed.starts_with(c)) {
+            comments += 1;
+            continue;
+        }
+
+        if let Some((start, end)) = syntax.block_comment {
+            if let Some(rest) = trimmed.strip_prefix(start) {
+                comments += 1;
+                match rest.find(end) {
+                    None => in_block = true,
+                    Some(index) if !rest[index + end.len()..].trim().is_empty() => {
+                        comments -= 1;
+                        code += 1;
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+            // A block comment opened after code on the same line
+            if let Some(index) = trimmed.find(start) {
+                in_block = !trimmed[index + start.len()..].contains(end);
+            }
+        }
+        code += 1;
+    }
+    (code, comments, blanks)
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Commit counts and line changes per file over the last `days` days, relative to `root`
+fn git_churn(root: &str, days: u32, limit: usize) -> Vec<ChurnedFile> {
+    let since = format!("--since={}.days", days);
+    let Ok(output) = run_git(root, &["log", &since, "--no-merges", "--numstat", "--relative", "--format=", "--", "."]) else {
+        return vec![];
+    };
+
+    let mut churn: HashMap<String, ChurnedFile> = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(additions), Some(deletions), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        // Renames are reported as `old => new`; their churn is attributed to neither side
+        if path.contains(" => ") {
+            continue;
+        }
+        let entry = churn.entry(path.to_string()).or_insert_with(|| ChurnedFile {
+            path: path.to_string(),
+            commits: 0,
+            additions: 0,
+            deletions: 0,
+        });
+        entry.commits += 1;
+        // Binary files report "-"
+        entry.additions += additions.parse::<usize>().unwrap_or(0);
+        entry.deletions += deletions.parse::<usize>().unwrap_or(0);
+    }
+
+    let mut churn: Vec<ChurnedFile> = churn
+        .into_values()
+        .filter(|f| Path::new(root).join(&f.path).exists())
+        .collect();
+    churn.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then((b.additions + b.deletions).cmp(&(a.additions + a.deletions)))
+            .then(a.path.cmp(&b.path))
+    });
+    churn.truncate(limit);
+    churn
+}
+
+/// Line counts per language, largest files and recent git churn for a project, honouring .gitignore
+#[tauri::command]
+pub async fn get_codebase_stats(
+    project_path: String,
+    top: Option<usize>,
+    churn_days: Option<u32>,
+) -> Result<CodebaseStats, String> {
+    tauri::async_runtime::spawn_blocking(move || codebase_stats(&project_path, top, churn_days))
+        .await
+        .map_err(|e| format!("Stats task failed: {}", e))?
+}
+
+fn codebase_stats(project_path: &str, top: Option<usize>, churn_days: Option<u32>) -> Result<CodebaseStats, String> {
+    let started = Instant::now();
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(format!("Project not found: {}", project_path));
+    }
+    let top = top.unwrap_or(DEFAULT_TOP_FILES);
+    let churn_days = churn_days.unwrap_or(DEFAULT_CHURN_DAYS);
+
+    let mut languages: HashMap<&'static str, LanguageStats> = HashMap::new();
+    let mut sizes: Vec<FileSize> = Vec::new();
+    let mut files = 0;
+    let mut bytes = 0;
+
+    for entry in WalkBuilder::new(root).build().flatten() {
+        if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        files += 1;
+        bytes += metadata.len();
+
+        let syntax = syntax_for(path);
+        let mut lines = None;
+        if let Some(ref syntax) = syntax {
+            if metadata.len() <= MAX_COUNTED_BYTES {
+                if let Ok(source) = fs::read_to_string(path) {
+                    let (code, comments, blanks) = count_lines(&source, syntax);
+                    let stats = languages.entry(syntax.name).or_insert_with(|| LanguageStats {
+                        language: syntax.name.to_string(),
+                        ..Default::default()
+                    });
+                    stats.files += 1;
+                    stats.code += code;
+                    stats.comments += comments;
+                    stats.blanks += blanks;
+                    lines = Some(code + comments + blanks);
+                }
+            }
+        }
+
+        sizes.push(FileSize {
+            path: relative_path(root, path),
+            bytes: metadata.len(),
+            lines,
+            language: syntax.map(|s| s.name.to_string()),
+        });
+    }
+
+    let mut languages: Vec<LanguageStats> = languages.into_values().collect();
+    languages.sort_by(|a, b| b.code.cmp(&a.code).then(a.language.cmp(&b.language)));
+    let total = languages.iter().fold(
+        LanguageStats {
+            language: "Total".to_string(),
+            ..Default::default()
+        },
+        |mut total, l| {
+            total.files += l.files;
+            total.code += l.code;
+            total.comments += l.comments;
+            total.blanks += l.blanks;
+            total
+        },
+    );
+
+    sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.path.cmp(&b.path)));
+    sizes.truncate(top);
+
+    let churn = if is_git_repo(project_path) {
+        git_churn(project_path, churn_days, top)
+    } else {
+        vec![]
+    };
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    println!(
+        "[DEBUG] Counted {} lines of code in {} files in {}ms",
+        total.code, files, duration_ms
+    );
+    Ok(CodebaseStats {
+        languages,
+        total,
+        files,
+        bytes,
+        largest_files: sizes,
+        churn,
+        churn_days,
+        duration_ms,
+    })
+}
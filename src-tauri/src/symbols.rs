@@ -0,0 +1,442 @@
+use crate::git::is_gitignored;
+use crate::outline::{
+    find_identifier_ranges, identifier_at, outline_source, OutlineLanguage, OutlineSymbol, SymbolRange,
+    MAX_OUTLINE_BYTES,
+};
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+
+/// Default number of results for symbol searches
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Reference searches stop after this many hits
+const MAX_REFERENCES: usize = 1000;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub kind: String,
+    /// Project-relative path with forward slashes
+    pub path: String,
+    pub range: SymbolRange,
+    pub selection_range: SymbolRange,
+    /// Name of the enclosing symbol, e.g. the class of a method
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SymbolReference {
+    pub path: String,
+    pub range: SymbolRange,
+    /// The full source line containing the reference
+    pub line_text: String,
+    pub is_definition: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IndexStats {
+    pub files: usize,
+    pub symbols: usize,
+    /// Files (re)parsed by this call; unchanged files are skipped
+    pub parsed: usize,
+    pub duration_ms: u64,
+}
+
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    symbols: Vec<IndexedSymbol>,
+}
+
+#[derive(Default)]
+struct ProjectIndex {
+    files: HashMap<String, IndexedFile>,
+}
+
+/// Workspace symbol indexes, keyed by project root
+#[derive(Default)]
+pub struct SymbolIndexState {
+    projects: Mutex<HashMap<String, ProjectIndex>>,
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+fn flatten(symbols: Vec<OutlineSymbol>, path: &str, container: Option<&str>, out: &mut Vec<IndexedSymbol>) {
+    for symbol in symbols {
+        // Headings are document structure, not code symbols
+        if symbol.kind == "heading" {
+            continue;
+        }
+        flatten(symbol.children, path, Some(&symbol.name), out);
+        out.push(IndexedSymbol {
+            name: symbol.name,
+            kind: symbol.kind,
+            path: path.to_string(),
+            range: symbol.range,
+            selection_range: symbol.selection_range,
+            container: container.map(|c| c.to_string()),
+            detail: symbol.detail,
+        });
+    }
+}
+
+/// Parse one file into index entries; `None` when it cannot or should not be indexed
+fn index_file(root: &Path, path: &Path) -> Option<(String, IndexedFile)> {
+    let language = OutlineLanguage::from_path(path)?;
+    if language == OutlineLanguage::Markdown {
+        return None;
+    }
+    let relative = relative_path(root, path)?;
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() as usize > MAX_OUTLINE_BYTES {
+        return None;
+    }
+    let source = fs::read_to_string(path).ok()?;
+    let outline = outline_source(language, &source).ok()?;
+
+    let mut symbols = Vec::new();
+    flatten(outline, &relative, None, &mut symbols);
+    Some((
+        relative,
+        IndexedFile {
+            modified: metadata.modified().ok(),
+            symbols,
+        },
+    ))
+}
+
+/// Source files under `root` that the index covers, honouring .gitignore
+fn source_files(root: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|entry| entry.into_path())
+        .filter(|path| matches!(OutlineLanguage::from_path(path), Some(l) if l != OutlineLanguage::Markdown))
+        .collect()
+}
+
+fn canonical_root(project_path: &str) -> Result<String, String> {
+    Path::new(project_path)
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to resolve project path: {}", e))
+}
+
+/// Bring the index for `root` up to date, reparsing only files whose mtime changed. Parsing
+/// happens without holding the index lock so queries keep working while a project is indexed.
+fn refresh_index(state: &SymbolIndexState, root: &str) -> IndexStats {
+    let started = Instant::now();
+    let root_path = Path::new(root);
+    let files = source_files(root_path);
+
+    let known: HashMap<String, Option<SystemTime>> = state
+        .projects
+        .lock()
+        .unwrap()
+        .get(root)
+        .map(|index| index.files.iter().map(|(path, f)| (path.clone(), f.modified)).collect())
+        .unwrap_or_default();
+
+    let mut seen = std::collections::HashSet::new();
+    let mut reparsed = Vec::new();
+    for path in files {
+        let Some(relative) = relative_path(root_path, &path) else {
+            continue;
+        };
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let unchanged = known
+            .get(&relative)
+            .map(|m| m.is_some() && *m == modified)
+            .unwrap_or(false);
+        if !unchanged {
+            if let Some(entry) = index_file(root_path, &path) {
+                reparsed.push(entry);
+            }
+        }
+        seen.insert(relative);
+    }
+
+    let parsed = reparsed.len();
+    let mut projects = state.projects.lock().unwrap();
+    let index = projects.entry(root.to_string()).or_default();
+    index.files.extend(reparsed);
+    index.files.retain(|path, _| seen.contains(path));
+
+    IndexStats {
+        files: index.files.len(),
+        symbols: index.files.values().map(|f| f.symbols.len()).sum(),
+        parsed,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Index `root` on first use so queries work without an explicit indexing step
+fn ensure_index(state: &SymbolIndexState, root: &str) {
+    let indexed = state.projects.lock().unwrap().contains_key(root);
+    if !indexed {
+        refresh_index(state, root);
+    }
+}
+
+/// Whether the full scan would pick up `path`: `source_files` skips hidden and ignored files
+fn is_indexed_path(root: &Path, path: &Path) -> bool {
+    let hidden = path
+        .strip_prefix(root)
+        .map(|relative| relative.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.')))
+        .unwrap_or(true);
+    !hidden && !is_gitignored(root, path)
+}
+
+/// Apply file watcher changes to an existing index; projects that were never indexed are ignored
+pub fn update_paths(app: &AppHandle, root: &str, paths: &[PathBuf]) {
+    let state = app.state::<SymbolIndexState>();
+    if !state.projects.lock().unwrap().contains_key(root) {
+        return;
+    }
+
+    let root_path = Path::new(root);
+    let mut changes = Vec::new();
+    for path in paths {
+        let Some(relative) = relative_path(root_path, path) else {
+            continue;
+        };
+        let file = is_indexed_path(root_path, path)
+            .then(|| index_file(root_path, path))
+            .flatten()
+            .map(|(_, file)| file);
+        changes.push((relative, file));
+    }
+
+    let mut projects = state.projects.lock().unwrap();
+    let Some(index) = projects.get_mut(root) else {
+        return;
+    };
+    for (relative, file) in changes {
+        match file {
+            Some(file) => {
+                index.files.insert(relative, file);
+            }
+            None => {
+                index.files.remove(&relative);
+            }
+        }
+    }
+}
+
+/// Score how well `name` matches `query`; higher is better, `None` is no match
+fn match_score(name: &str, query: &str) -> Option<u32> {
+    if name == query {
+        return Some(100);
+    }
+    let (lower_name, lower_query) = (name.to_lowercase(), query.to_lowercase());
+    if lower_name == lower_query {
+        return Some(90);
+    }
+    if lower_name.starts_with(&lower_query) {
+        return Some(80);
+    }
+    if lower_name.contains(&lower_query) {
+        return Some(60);
+    }
+    // Fuzzy subsequence match, e.g. "gdo" for "get_document_outline"
+    let mut chars = lower_name.chars();
+    if lower_query.chars().all(|q| chars.any(|c| c == q)) {
+        return Some(20);
+    }
+    None
+}
+
+#[tauri::command]
+pub async fn index_project_symbols(app: AppHandle, project_path: String) -> Result<IndexStats, String> {
+    let root = canonical_root(&project_path)?;
+    let stats = tauri::async_runtime::spawn_blocking(move || refresh_index(&app.state::<SymbolIndexState>(), &root))
+        .await
+        .map_err(|e| format!("Indexing task failed: {}", e))?;
+    println!(
+        "[DEBUG] Indexed {} symbols in {} files ({} parsed) in {}ms",
+        stats.symbols, stats.files, stats.parsed, stats.duration_ms
+    );
+    Ok(stats)
+}
+
+/// Search workspace symbols by name (exact, prefix, substring, then fuzzy)
+#[tauri::command]
+pub async fn find_symbols(
+    app: AppHandle,
+    project_path: String,
+    query: String,
+    kind: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<IndexedSymbol>, String> {
+    let root = canonical_root(&project_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        search_symbols(&app.state::<SymbolIndexState>(), &root, &query, kind.as_deref(), limit)
+    })
+    .await
+    .map_err(|e| format!("Symbol search failed: {}", e))?
+}
+
+fn search_symbols(
+    state: &SymbolIndexState,
+    root: &str,
+    query: &str,
+    kind: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<IndexedSymbol>, String> {
+    ensure_index(state, root);
+
+    let projects = state.projects.lock().unwrap();
+    let index = projects.get(root).ok_or_else(|| "Project is not indexed".to_string())?;
+
+    let mut matches: Vec<(u32, &IndexedSymbol)> = index
+        .files
+        .values()
+        .flat_map(|f| f.symbols.iter())
+        .filter(|s| kind.map(|k| s.kind == k).unwrap_or(true))
+        .filter_map(|s| match_score(&s.name, query).map(|score| (score, s)))
+        .collect();
+    matches.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.1.name.len().cmp(&b.1.name.len()))
+            .then_with(|| a.1.path.cmp(&b.1.path))
+    });
+
+    Ok(matches
+        .into_iter()
+        .take(limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .map(|(_, s)| s.clone())
+        .collect())
+}
+
+/// Definitions of the identifier at a position. `content` is the editor buffer if it has
+/// unsaved changes. Definitions in the same file are listed first.
+#[tauri::command]
+pub async fn go_to_definition(
+    app: AppHandle,
+    project_path: String,
+    path: String,
+    line: usize,
+    column: usize,
+    content: Option<String>,
+) -> Result<Vec<IndexedSymbol>, String> {
+    let root = canonical_root(&project_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        find_definitions(&app.state::<SymbolIndexState>(), &root, &path, line, column, content)
+    })
+    .await
+    .map_err(|e| format!("Definition search failed: {}", e))?
+}
+
+fn find_definitions(
+    state: &SymbolIndexState,
+    root: &str,
+    path: &str,
+    line: usize,
+    column: usize,
+    content: Option<String>,
+) -> Result<Vec<IndexedSymbol>, String> {
+    let language = OutlineLanguage::from_path(Path::new(path))
+        .ok_or_else(|| format!("Unsupported file type: {}", path))?;
+    let source = match content {
+        Some(content) => content,
+        None => fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+    let Some(name) = identifier_at(language, &source, line, column) else {
+        return Ok(vec![]);
+    };
+
+    ensure_index(state, root);
+    let current = Path::new(path)
+        .canonicalize()
+        .ok()
+        .and_then(|p| relative_path(Path::new(root), &p));
+
+    let projects = state.projects.lock().unwrap();
+    let index = projects.get(root).ok_or_else(|| "Project is not indexed".to_string())?;
+    let mut definitions: Vec<IndexedSymbol> = index
+        .files
+        .values()
+        .flat_map(|f| f.symbols.iter())
+        .filter(|s| s.name == name)
+        .cloned()
+        .collect();
+    definitions.sort_by_key(|s| (Some(&s.path) != current.as_ref(), s.path.clone(), s.range.start_line));
+    Ok(definitions)
+}
+
+/// Best-effort references: identifier tokens with the same spelling across indexed files,
+/// ignoring matches in comments and strings
+#[tauri::command]
+pub async fn find_references(app: AppHandle, project_path: String, name: String) -> Result<Vec<SymbolReference>, String> {
+    let root = canonical_root(&project_path)?;
+    tauri::async_runtime::spawn_blocking(move || collect_references(&app.state::<SymbolIndexState>(), &root, &name))
+        .await
+        .map_err(|e| format!("Reference search failed: {}", e))?
+}
+
+fn collect_references(state: &SymbolIndexState, root: &str, name: &str) -> Result<Vec<SymbolReference>, String> {
+    ensure_index(state, root);
+
+    // Snapshot what we need so parsing happens without holding the lock
+    let (files, definitions): (Vec<String>, Vec<(String, SymbolRange)>) = {
+        let projects = state.projects.lock().unwrap();
+        let index = projects.get(root).ok_or_else(|| "Project is not indexed".to_string())?;
+        let mut files: Vec<String> = index.files.keys().cloned().collect();
+        files.sort();
+        let definitions = index
+            .files
+            .values()
+            .flat_map(|f| f.symbols.iter())
+            .filter(|s| s.name == name)
+            .map(|s| (s.path.clone(), s.selection_range.clone()))
+            .collect();
+        (files, definitions)
+    };
+
+    let mut references = Vec::new();
+    for relative in files {
+        let absolute = Path::new(root).join(&relative);
+        let Some(language) = OutlineLanguage::from_path(&absolute) else {
+            continue;
+        };
+        let Ok(source) = fs::read_to_string(&absolute) else {
+            continue;
+        };
+        // Cheap textual filter before parsing
+        if !source.contains(name) {
+            continue;
+        }
+        let lines: Vec<&str> = source.lines().collect();
+        for range in find_identifier_ranges(language, &source, name) {
+            let is_definition = definitions.iter().any(|(p, r)| p == &relative && r == &range);
+            references.push(SymbolReference {
+                path: relative.clone(),
+                line_text: lines.get(range.start_line - 1).unwrap_or(&"").to_string(),
+                range,
+                is_definition,
+            });
+            if references.len() >= MAX_REFERENCES {
+                return Ok(references);
+            }
+        }
+    }
+    Ok(references)
+}
@@ -0,0 +1,419 @@
+use crate::shell::ShellConfig;
+use crate::terminal::{spawn_terminal, ExitInfo, StartupOptions, TerminalExit, TerminalState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use uuid::Uuid;
+
+/// Custom tasks defined by the project
+const PROJECT_TASKS_PATH: &str = ".milhouse/tasks.json";
+
+/// Finished runs kept in memory
+const MAX_RUN_HISTORY: usize = 100;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RunnableTask {
+    /// Stable id, `<source>:<name>`
+    pub id: String,
+    pub name: String,
+    /// "npm", "cargo", "make", "just" or "custom"
+    pub source: String,
+    /// Shell command line that runs the task
+    pub command: String,
+    pub cwd: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CustomTask {
+    name: String,
+    command: String,
+    /// Relative to the project root
+    cwd: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// The task was killed, e.g. by closing its terminal
+    Cancelled,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TaskRun {
+    pub id: String,
+    pub project_path: String,
+    pub task: RunnableTask,
+    pub terminal_id: String,
+    pub status: TaskRunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Milliseconds since the epoch
+    pub started_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct TaskRunnerState {
+    runs: Mutex<Vec<TaskRun>>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Quote a string for a POSIX shell command line, leaving simple words alone
+fn shell_word(s: &str) -> String {
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.:/@+=".contains(c)) {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+fn task(source: &str, name: &str, command: String, cwd: &str, description: Option<String>) -> RunnableTask {
+    RunnableTask {
+        id: format!("{}:{}", source, name),
+        name: name.to_string(),
+        source: source.to_string(),
+        command,
+        cwd: cwd.to_string(),
+        description,
+    }
+}
+
+/// `package.json` scripts, run with the package manager whose lockfile is present
+fn npm_tasks(root: &Path, cwd: &str) -> Vec<RunnableTask> {
+    let Ok(content) = fs::read_to_string(root.join("package.json")) else {
+        return vec![];
+    };
+    let Ok(package) = serde_json::from_str::<Value>(&content) else {
+        return vec![];
+    };
+    let runner = if root.join("pnpm-lock.yaml").exists() {
+        "pnpm run"
+    } else if root.join("yarn.lock").exists() {
+        "yarn run"
+    } else if root.join("bun.lockb").exists() || root.join("bun.lock").exists() {
+        "bun run"
+    } else {
+        "npm run"
+    };
+
+    let Some(scripts) = package["scripts"].as_object() else {
+        return vec![];
+    };
+    scripts
+        .iter()
+        .map(|(name, script)| {
+            task(
+                "npm",
+                name,
+                format!("{} {}", runner, shell_word(name)),
+                cwd,
+                script.as_str().map(|s| s.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Standard cargo commands plus a run task per binary and example target
+fn cargo_tasks(root: &Path, cwd: &str) -> Vec<RunnableTask> {
+    if !root.join("Cargo.toml").exists() {
+        return vec![];
+    }
+    let mut tasks: Vec<RunnableTask> = ["build", "check", "test", "clippy"]
+        .iter()
+        .map(|name| task("cargo", name, format!("cargo {}", name), cwd, None))
+        .collect();
+
+    let metadata = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(root)
+        .output();
+    let Ok(output) = metadata else {
+        return tasks;
+    };
+    let Ok(metadata) = serde_json::from_slice::<Value>(&output.stdout) else {
+        return tasks;
+    };
+
+    let packages = metadata["packages"].as_array().cloned().unwrap_or_default();
+    let multiple_packages = packages.len() > 1;
+    for package in &packages {
+        let package_name = package["name"].as_str().unwrap_or("");
+        for target in package["targets"].as_array().into_iter().flatten() {
+            let Some(target_name) = target["name"].as_str() else {
+                continue;
+            };
+            let kinds: Vec<&str> = target["kind"].as_array().into_iter().flatten().filter_map(|k| k.as_str()).collect();
+            let (label, flag) = if kinds.contains(&"bin") {
+                ("run", "--bin")
+            } else if kinds.contains(&"example") {
+                ("example", "--example")
+            } else {
+                continue;
+            };
+            let mut command = "cargo run".to_string();
+            if multiple_packages {
+                command.push_str(&format!(" -p {}", shell_word(package_name)));
+            }
+            command.push_str(&format!(" {} {}", flag, shell_word(target_name)));
+            tasks.push(task("cargo", &format!("{} {}", label, target_name), command, cwd, None));
+        }
+    }
+    tasks
+}
+
+/// Explicit targets from a Makefile; pattern rules and special targets are skipped
+fn make_tasks(root: &Path, cwd: &str) -> Vec<RunnableTask> {
+    let Some(content) = ["GNUmakefile", "makefile", "Makefile"]
+        .iter()
+        .find_map(|name| fs::read_to_string(root.join(name)).ok())
+    else {
+        return vec![];
+    };
+
+    let mut names: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(['\t', ' ', '#', '.']) {
+            continue;
+        }
+        let Some((targets, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `VAR := value` and `VAR ::= value` are assignments, not rules
+        if rest.starts_with('=') || rest.starts_with(":=") || targets.contains('=') {
+            continue;
+        }
+        for target in targets.split_whitespace() {
+            if !target.contains(['%', '$']) && !names.iter().any(|n| n == target) {
+                names.push(target.to_string());
+            }
+        }
+    }
+    names
+        .iter()
+        .map(|name| task("make", name, format!("make {}", shell_word(name)), cwd, None))
+        .collect()
+}
+
+/// Recipes from a justfile; private recipes (leading underscore) are skipped
+fn just_tasks(root: &Path, cwd: &str) -> Vec<RunnableTask> {
+    let Some(content) = ["justfile", "Justfile", ".justfile"]
+        .iter()
+        .find_map(|name| fs::read_to_string(root.join(name)).ok())
+    else {
+        return vec![];
+    };
+
+    let mut tasks = Vec::new();
+    let mut comment: Option<String> = None;
+    for line in content.lines() {
+        if let Some(doc) = line.strip_prefix('#') {
+            comment = Some(doc.trim().to_string());
+            continue;
+        }
+        let previous_comment = comment.take();
+        if line.starts_with([' ', '\t', '[']) || line.trim().is_empty() {
+            continue;
+        }
+        let Some((header, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') {
+            continue;
+        }
+        let mut words = header.split_whitespace();
+        let Some(name) = words.next().map(|n| n.trim_start_matches('@')) else {
+            continue;
+        };
+        let is_keyword = matches!(name, "set" | "alias" | "export" | "import" | "mod");
+        if is_keyword || name.starts_with('_') || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            continue;
+        }
+        tasks.push(task("just", name, format!("just {}", name), cwd, previous_comment));
+    }
+    tasks
+}
+
+fn custom_tasks(root: &Path) -> Result<Vec<RunnableTask>, String> {
+    let path = root.join(PROJECT_TASKS_PATH);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read tasks config: {}", e))?;
+    let tasks: Vec<CustomTask> =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse tasks config: {}", e))?;
+    Ok(tasks
+        .into_iter()
+        .map(|t| {
+            let cwd = match t.cwd {
+                Some(dir) => root.join(dir).to_string_lossy().to_string(),
+                None => root.to_string_lossy().to_string(),
+            };
+            task("custom", &t.name, t.command, &cwd, t.description)
+        })
+        .collect())
+}
+
+fn discover_tasks(project_path: &str) -> Result<Vec<RunnableTask>, String> {
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(format!("Project not found: {}", project_path));
+    }
+    let mut tasks = custom_tasks(root)?;
+    tasks.extend(npm_tasks(root, project_path));
+    tasks.extend(cargo_tasks(root, project_path));
+    tasks.extend(make_tasks(root, project_path));
+    tasks.extend(just_tasks(root, project_path));
+    Ok(tasks)
+}
+
+/// Record how a run's process ended; later reports for the same run are ignored
+fn finish_run(app: &AppHandle, run_id: &str, exit: &ExitInfo) {
+    let state = app.state::<TaskRunnerState>();
+    let mut runs = state.runs.lock().unwrap();
+    let Some(run) = runs.iter_mut().find(|r| r.id == run_id && r.status == TaskRunStatus::Running) else {
+        return;
+    };
+    let exit_code = exit.exit_code.map(|code| code as i32);
+    let finished_at = now_millis();
+    run.status = match exit_code {
+        Some(0) => TaskRunStatus::Succeeded,
+        Some(_) => TaskRunStatus::Failed,
+        None => TaskRunStatus::Cancelled,
+    };
+    run.exit_code = exit_code;
+    run.finished_at = Some(finished_at);
+    run.duration_ms = Some(finished_at.saturating_sub(run.started_at));
+    println!("[DEBUG] Task run {} finished: {:?}", run_id, run.status);
+    let _ = app.emit("task-run-updated", run.clone());
+}
+
+/// Finish a run when its terminal reports how the task exited
+fn track_run(app: &AppHandle, run_id: &str, terminal_id: &str) {
+    let app_for_exit = app.clone();
+    let (run_for_exit, terminal_for_exit) = (run_id.to_string(), terminal_id.to_string());
+    let listener = Arc::new(Mutex::new(None));
+    let listener_for_handler = listener.clone();
+    let event_id = app.listen("terminal-exit", move |event| {
+        let Ok(exited) = serde_json::from_str::<TerminalExit>(event.payload()) else {
+            return;
+        };
+        if exited.id == terminal_for_exit {
+            finish_run(&app_for_exit, &run_for_exit, &exited.exit);
+            if let Some(event_id) = listener_for_handler.lock().unwrap().take() {
+                app_for_exit.unlisten(event_id);
+            }
+        }
+    });
+    *listener.lock().unwrap() = Some(event_id);
+
+    // A quick task may have exited before the listener was registered
+    let exited = {
+        let terminals = app.state::<TerminalState>();
+        let terminals = terminals.terminals.lock().unwrap();
+        terminals.get(terminal_id).and_then(|t| t.exit.lock().unwrap().clone())
+    };
+    if let Some(exited) = exited {
+        finish_run(app, run_id, &exited.exit);
+        if let Some(event_id) = listener.lock().unwrap().take() {
+            app.unlisten(event_id);
+        }
+    }
+}
+
+/// Tasks discovered from package.json, Cargo.toml, Makefile, justfile and `.milhouse/tasks.json`
+#[tauri::command]
+pub async fn list_runnable_tasks(project_path: String) -> Result<Vec<RunnableTask>, String> {
+    tauri::async_runtime::spawn_blocking(move || discover_tasks(&project_path))
+        .await
+        .map_err(|e| format!("Task discovery failed: {}", e))?
+}
+
+/// Run a discovered task in a new terminal
+#[tauri::command]
+pub async fn run_task(app: AppHandle, project_path: String, task_id: String) -> Result<TaskRun, String> {
+    tauri::async_runtime::spawn_blocking(move || start_run(&app, project_path, &task_id))
+        .await
+        .map_err(|e| format!("Failed to run task: {}", e))?
+}
+
+fn start_run(app: &AppHandle, project_path: String, task_id: &str) -> Result<TaskRun, String> {
+    let task = discover_tasks(&project_path)?
+        .into_iter()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task not found: {}", task_id))?;
+
+    let run_id = Uuid::new_v4().simple().to_string()[..8].to_string();
+    println!("[DEBUG] Running task {}: {}", task.id, task.command);
+    // Run the task as the terminal's program so its exit status is the terminal's
+    let terminal_id = spawn_terminal(
+        &app.state::<TerminalState>(),
+        app,
+        Some(task.cwd.clone()),
+        Some(task.command.clone()),
+        &ShellConfig::default(),
+        StartupOptions {
+            direct: true,
+            ..Default::default()
+        },
+        false,
+    )?;
+
+    let run = TaskRun {
+        id: run_id.clone(),
+        project_path,
+        task,
+        terminal_id: terminal_id.clone(),
+        status: TaskRunStatus::Running,
+        exit_code: None,
+        started_at: now_millis(),
+        finished_at: None,
+        duration_ms: None,
+    };
+    {
+        let state = app.state::<TaskRunnerState>();
+        let mut runs = state.runs.lock().unwrap();
+        runs.push(run.clone());
+        if runs.len() > MAX_RUN_HISTORY {
+            // Drop the oldest finished run; running ones are never evicted
+            if let Some(index) = runs.iter().position(|r| r.status != TaskRunStatus::Running) {
+                runs.remove(index);
+            }
+        }
+    }
+    let _ = app.emit("task-run-updated", run.clone());
+
+    track_run(app, &run_id, &terminal_id);
+    Ok(run)
+}
+
+/// Task runs, newest first
+#[tauri::command]
+pub fn list_task_runs(state: State<'_, TaskRunnerState>, project_path: Option<String>) -> Result<Vec<TaskRun>, String> {
+    let runs = state.runs.lock().unwrap();
+    Ok(runs
+        .iter()
+        .rev()
+        .filter(|r| project_path.is_none() || project_path.as_ref() == Some(&r.project_path))
+        .cloned()
+        .collect())
+}
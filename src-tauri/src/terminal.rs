@@ -1,15 +1,153 @@
-use portable_pty::{native_pty_system, CommandBuilder, PtySize, PtyPair};
-use serde::Serialize;
-use std::collections::HashMap;
+use crate::checkpoint::create_checkpoint_internal;
+use crate::emulator::{ScreenSnapshot, TerminalScreen};
+use crate::recording::{Recorder, RecordingOptions};
+use crate::git::is_git_repo;
+use crate::sessions::{
+    attach_session, create_session, is_session, kill_session, record_session, resize_session, session_history, session_screen,
+    stop_session_recording, write_session, SessionState,
+};
+use crate::shell::{resolve_shell, ShellConfig};
+use crate::shell_integration::{emit_events, CommandHistory, ShellIntegration};
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, ExitStatus, MasterPty, PtySize, PtyPair};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use tauri::{AppHandle, Emitter, State};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
 use uuid::Uuid;
 
+/// Output kept per terminal for replay after the webview reloads
+const MAX_SCROLLBACK_BYTES: usize = 2 * 1024 * 1024;
+
+/// Time hung-up processes get to exit before they are sent SIGKILL
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+/// Time allowed for the shell to be reaped after SIGKILL
+const REAP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Lines of output included in `terminal-exit`
+const EXIT_TAIL_LINES: usize = 10;
+/// Output searched for those lines
+const EXIT_TAIL_BYTES: usize = 16 * 1024;
+/// Time the reader gets to pick up the shell's final output before the exit is reported
+pub const EXIT_DRAIN_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest wait for the shell to become ready before the startup command is typed anyway
+const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Without a prompt marker or pattern, the shell counts as ready once its output pauses this long
+const READY_QUIET_PERIOD: Duration = Duration::from_millis(250);
+/// Output kept for matching a ready pattern across reads
+const READY_TAIL_BYTES: usize = 4096;
+/// OSC 133 prompt start and command start markers from shell integration
+const PROMPT_MARKERS: [&str; 2] = ["\x1b]133;A", "\x1b]133;B"];
+
 pub struct TerminalInstance {
     pub pty_pair: PtyPair,
     pub writer: Box<dyn Write + Send>,
+    pub scrollback: Arc<Mutex<Scrollback>>,
+    pub pid: Option<u32>,
+    pub started_at: u64,
+    pub killer: Box<dyn ChildKiller + Send + Sync>,
+    /// Set by the exit watcher once the shell has been reaped; kept until the terminal is killed
+    pub exit: Arc<Mutex<Option<TerminalExit>>>,
+    /// Command boundaries and cwd reported by the shell
+    pub integration: Arc<Mutex<ShellIntegration>>,
+    pub screen: Arc<Mutex<TerminalScreen>>,
+    /// Only set while recording; taken after `screen` when both are locked
+    pub recorder: Arc<Mutex<Option<Recorder>>>,
+}
+
+/// Bounded ring of recent output. Offsets count bytes emitted since the terminal started,
+/// so a client can tell replayed output from live events.
+#[derive(Default)]
+pub struct Scrollback {
+    chunks: VecDeque<String>,
+    len: usize,
+    end_offset: u64,
+}
+
+impl Scrollback {
+    /// Append output, dropping the oldest chunks past the limit; returns the new end offset
+    pub fn push(&mut self, data: &str) -> u64 {
+        self.chunks.push_back(data.to_string());
+        self.len += data.len();
+        self.end_offset += data.len() as u64;
+        while self.len > MAX_SCROLLBACK_BYTES && self.chunks.len() > 1 {
+            if let Some(dropped) = self.chunks.pop_front() {
+                self.len -= dropped.len();
+            }
+        }
+        self.end_offset
+    }
+
+    pub fn contents(&self) -> String {
+        self.chunks.iter().map(|c| c.as_str()).collect()
+    }
+
+    pub fn end_offset(&self) -> u64 {
+        self.end_offset
+    }
+
+    /// The last `count` non-blank lines of output as plain text
+    pub fn last_lines(&self, count: usize) -> Vec<String> {
+        let mut tail: Vec<&str> = Vec::new();
+        let mut len = 0;
+        for chunk in self.chunks.iter().rev() {
+            tail.push(chunk);
+            len += chunk.len();
+            if len >= EXIT_TAIL_BYTES {
+                break;
+            }
+        }
+        tail.reverse();
+        let text = strip_escapes(&tail.concat());
+        let lines: Vec<String> = text
+            .split('\n')
+            // A carriage return without a newline redraws the line, as progress bars do
+            .filter_map(|line| line.rsplit('\r').find(|s| !s.trim().is_empty()))
+            .map(|line| line.trim_end().to_string())
+            .collect();
+        lines[lines.len().saturating_sub(count)..].to_vec()
+    }
+}
+
+/// Remove terminal escape sequences and control characters other than newlines and carriage returns
+pub fn strip_escapes(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters up to a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC and other strings, ended by BEL or ST (ESC \)
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Character set designations take one more character
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\n' | '\r' | '\t' => output.push(c),
+            c if c.is_control() => {}
+            c => output.push(c),
+        }
+    }
+    output
 }
 
 pub struct TerminalState {
@@ -28,56 +166,366 @@ impl Default for TerminalState {
 pub struct TerminalOutput {
     pub id: String,
     pub data: String,
+    /// Scrollback offset at the end of `data`
+    pub offset: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ExitInfo {
+    /// None when the process was killed by a signal or could not be reaped
+    pub exit_code: Option<u32>,
+    pub signal: Option<String>,
+}
+
+impl ExitInfo {
+    pub fn from_wait(result: std::io::Result<ExitStatus>) -> Self {
+        let Ok(status) = result else {
+            return Self::default();
+        };
+        // portable-pty only exposes the signal name through Display
+        match status.to_string().strip_prefix("Terminated by ") {
+            Some(signal) => Self {
+                exit_code: None,
+                signal: Some(signal.to_string()),
+            },
+            None => Self {
+                exit_code: Some(status.exit_code()),
+                signal: None,
+            },
+        }
+    }
+}
+
+/// Payload of `terminal-exit`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TerminalExit {
+    pub id: String,
+    #[serde(flatten)]
+    pub exit: ExitInfo,
+    pub duration_ms: u64,
+    /// Last non-blank lines of output, with escape sequences removed
+    pub last_lines: Vec<String>,
+}
+
+impl TerminalExit {
+    pub fn new(id: String, exit: ExitInfo, started: Instant, scrollback: &Scrollback) -> Self {
+        Self {
+            id,
+            exit,
+            duration_ms: started.elapsed().as_millis() as u64,
+            last_lines: scrollback.last_lines(EXIT_TAIL_LINES),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct TerminalInfo {
+    pub id: String,
+    pub pid: Option<u32>,
+    pub started_at: u64,
+    /// Current directory last reported by the shell
+    pub cwd: Option<String>,
+    /// Set once the shell has exited
+    pub exit: Option<TerminalExit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KillReport {
+    #[serde(flatten)]
+    pub exit: ExitInfo,
+    /// The grace period ran out and the processes were sent SIGKILL
+    pub forced: bool,
+}
+
+/// How a startup command is launched
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StartupOptions {
+    /// Regex matched against output (escape sequences removed) that means the shell is ready
+    pub ready_pattern: Option<String>,
+    pub ready_timeout_ms: Option<u64>,
+    /// Run the command as the PTY's program instead of typing it into an interactive shell
+    pub direct: bool,
+}
+
+/// Per-terminal launch settings for `create_terminal`
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TerminalOptions {
+    /// Named profile from app settings
+    pub profile: Option<String>,
+    /// Applied on top of the profile
+    pub shell: Option<ShellConfig>,
+    pub startup: StartupOptions,
+    /// Record the terminal to the project (`cwd`) from the start
+    pub record: Option<RecordingOptions>,
+}
+
+impl StartupOptions {
+    pub fn ready_timeout(&self) -> Duration {
+        self.ready_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_READY_TIMEOUT)
+    }
+}
+
+/// Watches shell output until it is ready for a startup command: an OSC 133 prompt marker,
+/// the configured pattern, or otherwise the first pause after output (the prompt)
+pub struct Readiness {
+    pattern: Option<Regex>,
+    state: Mutex<ReadyState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct ReadyState {
+    last_output: Option<Instant>,
+    marker_seen: bool,
+    pattern_matched: bool,
+    raw_tail: String,
+    text_tail: String,
+}
+
+/// Keep at most `max` bytes from the end of `s`, on a character boundary
+fn truncate_front(s: &mut String, max: usize) {
+    if s.len() > max {
+        let mut start = s.len() - max;
+        while !s.is_char_boundary(start) {
+            start += 1;
+        }
+        s.drain(..start);
+    }
+}
+
+impl Readiness {
+    pub fn new(pattern: Option<&str>) -> Result<Self, String> {
+        let pattern = pattern
+            .filter(|p| !p.is_empty())
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| format!("Invalid ready pattern: {}", e))?;
+        Ok(Self {
+            pattern,
+            state: Mutex::new(ReadyState::default()),
+            changed: Condvar::new(),
+        })
+    }
+
+    /// Feed output from the reader thread
+    pub fn observe(&self, data: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.marker_seen || state.pattern_matched {
+            return;
+        }
+        state.last_output = Some(Instant::now());
+
+        // Markers can be split across reads, so search the end of the previous chunk too
+        state.raw_tail.push_str(data);
+        state.marker_seen = PROMPT_MARKERS.iter().any(|m| state.raw_tail.contains(m));
+        truncate_front(&mut state.raw_tail, 16);
+
+        if let Some(ref pattern) = self.pattern {
+            let text = strip_escapes(data);
+            state.text_tail.push_str(&text);
+            truncate_front(&mut state.text_tail, READY_TAIL_BYTES);
+            state.pattern_matched = pattern.is_match(&state.text_tail);
+        }
+        self.changed.notify_all();
+    }
+
+    /// Block until the shell looks ready; returns what gave it away, or None on timeout
+    pub fn wait(&self, timeout: Duration) -> Option<&'static str> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.marker_seen {
+                return Some("prompt marker");
+            }
+            if state.pattern_matched {
+                return Some("ready pattern");
+            }
+            if self.pattern.is_none() {
+                if let Some(last) = state.last_output {
+                    if last.elapsed() >= READY_QUIET_PERIOD {
+                        return Some("first prompt");
+                    }
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            let poll = (deadline - now).min(Duration::from_millis(50));
+            state = self.changed.wait_timeout(state, poll).unwrap().0;
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct TerminalSnapshot {
+    pub id: String,
+    /// Buffered output to replay
+    pub data: String,
+    /// Live `terminal-output` events at or below this offset are already included in `data`
+    pub offset: u64,
+}
+
+/// Incremental UTF-8 decoder for PTY output. A multibyte character split across two reads
+/// is held back until the rest of it arrives instead of being replaced with U+FFFD.
+#[derive(Default)]
+pub struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decode as much of `bytes` as possible, keeping an incomplete trailing sequence
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut output = String::new();
+        let mut rest: &[u8] = &self.pending;
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    output.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // Safe: `from_utf8` just validated this prefix
+                    output.push_str(std::str::from_utf8(valid).unwrap());
+                    match e.error_len() {
+                        // Genuinely invalid bytes are replaced, as before
+                        Some(len) => {
+                            output.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // The input ends mid-character; wait for the next read
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending = rest.to_vec();
+        output
+    }
+
+    /// Flush whatever is left at end of stream
+    pub fn finish(&mut self) -> String {
+        let rest = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        rest
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
 #[tauri::command]
-pub fn create_terminal(
-    state: State<'_, TerminalState>,
+pub async fn create_terminal(
     app: AppHandle,
     cwd: Option<String>,
     startup_command: Option<String>,
+    options: Option<TerminalOptions>,
+    checkpoint: Option<bool>,
+    detached: Option<bool>,
 ) -> Result<String, String> {
     println!("[DEBUG] create_terminal called:");
     println!("[DEBUG]   cwd: {:?}", cwd);
     println!("[DEBUG]   startup_command: {:?}", startup_command);
+    // The checkpoint snapshots the whole worktree, and detached terminals talk to the session
+    // daemon, so neither may run on the UI thread
+    tauri::async_runtime::spawn_blocking(move || {
+        open_terminal(
+            &app,
+            cwd,
+            startup_command,
+            options.unwrap_or_default(),
+            checkpoint.unwrap_or(false),
+            detached.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to create terminal: {}", e))?
+}
 
-    let pty_system = native_pty_system();
-
-    let pair = pty_system
-        .openpty(PtySize {
-            rows: 24,
-            cols: 80,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| format!("Failed to open pty: {}", e))?;
+fn open_terminal(
+    app: &AppHandle,
+    cwd: Option<String>,
+    startup_command: Option<String>,
+    options: TerminalOptions,
+    checkpoint: bool,
+    detached: bool,
+) -> Result<String, String> {
+    let state = app.state::<TerminalState>();
+    let shell = resolve_shell(app, options.profile.as_deref(), options.shell.as_ref())?;
+    let project_path = cwd.clone();
 
-    let mut cmd = CommandBuilder::new_default_prog();
+    // Detached terminals live in the session daemon and survive the app closing
+    let id = if detached {
+        create_session(
+            app,
+            &app.state::<SessionState>(),
+            cwd,
+            startup_command,
+            shell,
+            options.startup,
+            checkpoint,
+        )?
+    } else {
+        spawn_terminal(&state, app, cwd, startup_command, &shell, options.startup, checkpoint)?
+    };
 
-    // Ensure PATH includes ~/.local/bin where claude is typically installed
-    if let Some(home) = dirs::home_dir() {
-        let local_bin = home.join(".local/bin");
-        if let Ok(current_path) = std::env::var("PATH") {
-            let new_path = format!("{}:{}", local_bin.display(), current_path);
-            cmd.env("PATH", new_path);
-            println!("[DEBUG] Set PATH to include ~/.local/bin");
+    if let Some(record) = options.record {
+        // The terminal is already running, so a recording failure is reported but not fatal
+        let result = project_path
+            .ok_or_else(|| "Recording needs a project directory".to_string())
+            .and_then(|project| record_terminal(&state.terminals, &id, &project, record));
+        if let Err(e) = result {
+            println!("[DEBUG] Failed to start recording terminal {}: {}", id, e);
         }
     }
+    Ok(id)
+}
 
-    let _working_dir = if let Some(ref dir) = cwd {
-        cmd.cwd(dir);
-        Some(dir.clone())
-    } else if let Some(home) = dirs::home_dir() {
-        cmd.cwd(&home);
-        home.to_str().map(|s| s.to_string())
+/// Spawn a shell in a new PTY and start streaming its output; shared by
+/// `create_terminal` and other subsystems that launch terminals themselves
+pub fn spawn_terminal(
+    state: &TerminalState,
+    app: &AppHandle,
+    cwd: Option<String>,
+    startup_command: Option<String>,
+    shell: &ShellConfig,
+    startup: StartupOptions,
+    checkpoint: bool,
+) -> Result<String, String> {
+    if checkpoint {
+        checkpoint_before_session(app, cwd.as_deref());
+    }
+
+    // A direct command is the PTY's program; otherwise it is typed in once the shell is ready
+    let (program, typed_command) = if startup.direct {
+        (startup_command, None)
     } else {
-        None
+        (None, startup_command)
+    };
+    let readiness = match typed_command {
+        Some(_) => Some(Arc::new(Readiness::new(startup.ready_pattern.as_deref())?)),
+        None => None,
     };
 
-    let mut child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn command: {}", e))?;
+    let (pair, mut child) = open_shell(cwd.as_deref(), program.as_deref(), shell)?;
+    let pid = child.process_id();
+    let killer = child.clone_killer();
+    let exit = Arc::new(Mutex::new(None));
+    let started = Instant::now();
 
     let terminal_id = Uuid::new_v4().to_string();
     let id_clone = terminal_id.clone();
@@ -94,6 +542,11 @@ pub fn create_terminal(
         .take_writer()
         .map_err(|e| format!("Failed to take writer: {}", e))?;
 
+    let scrollback = Arc::new(Mutex::new(Scrollback::default()));
+    let integration = Arc::new(Mutex::new(ShellIntegration::default()));
+    let screen = Arc::new(Mutex::new(TerminalScreen::default()));
+    let recorder = Arc::new(Mutex::new(None));
+
     // Store the terminal instance
     {
         let mut terminals = state.terminals.lock().unwrap();
@@ -102,31 +555,56 @@ pub fn create_terminal(
             TerminalInstance {
                 pty_pair: pair,
                 writer,
+                scrollback: scrollback.clone(),
+                pid,
+                started_at: now_millis(),
+                killer,
+                exit: exit.clone(),
+                integration: integration.clone(),
+                screen: screen.clone(),
+                recorder: recorder.clone(),
             },
         );
     }
 
     // Spawn thread to read output and emit events
     let app_clone = app.clone();
+    let scrollback_for_exit = scrollback.clone();
+    let readiness_for_reader = readiness.clone();
     thread::spawn(move || {
         let mut buffer = [0u8; 4096];
+        let mut decoder = Utf8Decoder::default();
         loop {
-            match reader.read(&mut buffer) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    // Skip empty data
-                    if !data.is_empty() {
-                        let _ = app_clone.emit(
-                            "terminal-output",
-                            TerminalOutput {
-                                id: id_clone.clone(),
-                                data,
-                            },
-                        );
+            let (data, eof) = match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => (decoder.finish(), true),
+                Ok(n) => (decoder.decode(&buffer[..n]), false),
+            };
+            // Skip empty data, e.g. a read holding only part of a character
+            if !data.is_empty() {
+                if let Some(ref readiness) = readiness_for_reader {
+                    readiness.observe(&data);
+                }
+                let events = integration.lock().unwrap().feed(&data);
+                {
+                    let mut screen = screen.lock().unwrap();
+                    screen.process(&data);
+                    if let Some(recorder) = recorder.lock().unwrap().as_mut() {
+                        recorder.output(&data);
                     }
                 }
-                Err(_) => break,
+                let offset = scrollback.lock().unwrap().push(&data);
+                let _ = app_clone.emit(
+                    "terminal-output",
+                    TerminalOutput {
+                        id: id_clone.clone(),
+                        data,
+                        offset,
+                    },
+                );
+                emit_events(&app_clone, &id_clone, events);
+            }
+            if eof {
+                break;
             }
         }
     });
@@ -135,23 +613,31 @@ pub fn create_terminal(
     let terminal_id_for_exit = terminal_id.clone();
     let app_for_exit = app.clone();
     thread::spawn(move || {
-        let _ = child.wait();
-        let _ = app_for_exit.emit("terminal-exit", terminal_id_for_exit);
+        let status = ExitInfo::from_wait(child.wait());
+        thread::sleep(EXIT_DRAIN_DELAY);
+        let report = TerminalExit::new(
+            terminal_id_for_exit,
+            status,
+            started,
+            &scrollback_for_exit.lock().unwrap(),
+        );
+        println!("[DEBUG] Terminal {} exited: {:?}", report.id, report.exit);
+        *exit.lock().unwrap() = Some(report.clone());
+        let _ = app_for_exit.emit("terminal-exit", report);
     });
 
-    // If a startup command is provided, send it after a brief delay
-    if let Some(cmd_to_run) = startup_command {
+    // If a startup command is provided, type it once the shell is ready
+    if let (Some(cmd_to_run), Some(readiness)) = (typed_command, readiness) {
         println!("[DEBUG] Spawning thread to execute startup command: {}", cmd_to_run);
         let state_clone = state.terminals.clone();
         let terminal_id_for_cmd = terminal_id.clone();
+        let timeout = startup.ready_timeout();
         thread::spawn(move || {
-            // Wait for shell to initialize and terminal to be resized
-            // This needs enough time for:
-            // 1. Shell to load profile (adds ~/.local/bin to PATH)
-            // 2. Frontend to initialize xterm and fit addon
-            // 3. Resize event to be sent to PTY
-            println!("[DEBUG] Startup command thread: waiting 2000ms for shell and resize...");
-            thread::sleep(std::time::Duration::from_millis(2000));
+            // The shell has to load its profile (which may add ~/.local/bin to PATH) first
+            match readiness.wait(timeout) {
+                Some(signal) => println!("[DEBUG] Startup command thread: shell ready ({})", signal),
+                None => println!("[DEBUG] Startup command thread: shell not ready after {:?}, sending anyway", timeout),
+            }
 
             println!("[DEBUG] Startup command thread: attempting to acquire lock...");
             let mut terminals = state_clone.lock().unwrap();
@@ -177,9 +663,89 @@ pub fn create_terminal(
     Ok(terminal_id)
 }
 
+/// Run a command line as the PTY program. `sh -c` only parses it: a single command replaces
+/// the shell, and a compound one keeps its exit status.
+#[cfg(unix)]
+fn direct_command(command: &str) -> CommandBuilder {
+    let mut cmd = CommandBuilder::new("/bin/sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn direct_command(command: &str) -> CommandBuilder {
+    let mut cmd = CommandBuilder::new("cmd.exe");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+/// Snapshot the project before a Claude session so it can be rolled back
+pub fn checkpoint_before_session(app: &AppHandle, cwd: Option<&str>) {
+    let Some(dir) = cwd else {
+        return;
+    };
+    if !is_git_repo(dir) {
+        return;
+    }
+    match create_checkpoint_internal(dir, "Before Claude session", false) {
+        Ok(Some(created)) => {
+            let _ = app.emit("checkpoint-created", created);
+        }
+        Ok(None) => {}
+        Err(e) => println!("[DEBUG] Failed to create checkpoint: {}", e),
+    }
+}
+
+/// Open a PTY and start the configured shell in it, or `command` in its place
+pub fn open_shell(
+    cwd: Option<&str>,
+    command: Option<&str>,
+    shell: &ShellConfig,
+) -> Result<(PtyPair, Box<dyn Child + Send + Sync>), String> {
+    let pty_system = native_pty_system();
+
+    let pair = pty_system
+        .openpty(PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open pty: {}", e))?;
+
+    let mut cmd = match command {
+        Some(command) => direct_command(command),
+        None => shell.command(),
+    };
+
+    // Ensure PATH includes ~/.local/bin where claude is typically installed
+    if let Some(home) = dirs::home_dir() {
+        let local_bin = home.join(".local/bin");
+        if let Ok(current_path) = std::env::var("PATH") {
+            let new_path = format!("{}:{}", local_bin.display(), current_path);
+            cmd.env("PATH", new_path);
+            println!("[DEBUG] Set PATH to include ~/.local/bin");
+        }
+    }
+    shell.apply_env(&mut cmd);
+
+    if let Some(dir) = cwd {
+        cmd.cwd(dir);
+    } else if let Some(home) = dirs::home_dir() {
+        cmd.cwd(&home);
+    }
+
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn command: {}", e))?;
+    Ok((pair, child))
+}
+
 #[tauri::command]
 pub fn write_terminal(
     state: State<'_, TerminalState>,
+    sessions: State<'_, SessionState>,
     id: String,
     data: String,
 ) -> Result<(), String> {
@@ -194,15 +760,20 @@ pub fn write_terminal(
             .writer
             .flush()
             .map_err(|e| format!("Failed to flush terminal: {}", e))?;
+        if let Some(recorder) = terminal.recorder.lock().unwrap().as_mut() {
+            recorder.input(&data);
+        }
         Ok(())
     } else {
-        Err(format!("Terminal not found: {}", id))
+        drop(terminals);
+        write_session(&sessions, &id, &data)
     }
 }
 
 #[tauri::command]
 pub fn resize_terminal(
     state: State<'_, TerminalState>,
+    sessions: State<'_, SessionState>,
     id: String,
     rows: u16,
     cols: u16,
@@ -221,26 +792,345 @@ pub fn resize_terminal(
                 pixel_height: 0,
             })
             .map_err(|e| format!("Failed to resize terminal: {}", e))?;
+        let mut screen = terminal.screen.lock().unwrap();
+        screen.resize(rows, cols);
+        if let Some(recorder) = terminal.recorder.lock().unwrap().as_mut() {
+            recorder.resize(rows, cols);
+        }
         println!("[DEBUG] resize_terminal success: {}x{}", cols, rows);
         Ok(())
     } else {
-        Err(format!("Terminal not found: {}", id))
+        drop(terminals);
+        resize_session(&sessions, &id, rows, cols)
+    }
+}
+
+/// Send `signal` to each process group
+#[cfg(unix)]
+fn signal_groups(groups: &[i32], signal: i32) {
+    for group in groups {
+        unsafe {
+            libc::kill(-group, signal);
+        }
     }
 }
 
+/// Whether any process is left in the given process groups
+#[cfg(unix)]
+fn groups_alive(groups: &[i32]) -> bool {
+    groups.iter().any(|group| unsafe { libc::kill(-group, 0) } == 0)
+}
+
+/// Stop everything running in a PTY and wait for the exit watcher to reap the shell. The
+/// shell's process group and the foreground job's are hung up and sent SIGTERM, then SIGKILL
+/// if anything in them is still alive after the grace period.
+pub fn terminate(
+    master: &dyn MasterPty,
+    pid: Option<u32>,
+    killer: &mut dyn ChildKiller,
+    exit: &Mutex<Option<TerminalExit>>,
+) -> Result<KillReport, String> {
+    let already_exited = exit.lock().unwrap().is_some();
+
+    #[cfg(unix)]
+    let groups = {
+        // The shell leads its own session, so its pid is also its process group id. Once it
+        // has been reaped the id may be reused, so only the PTY's foreground group is trusted.
+        let mut groups: Vec<i32> = pid
+            .filter(|_| !already_exited)
+            .map(|pid| pid as i32)
+            .into_iter()
+            .collect();
+        if let Some(foreground) = master.process_group_leader() {
+            if !groups.contains(&foreground) {
+                groups.push(foreground);
+            }
+        }
+        groups
+    };
+
+    #[cfg(unix)]
+    {
+        if groups.is_empty() && !already_exited {
+            let _ = killer.kill();
+        }
+        signal_groups(&groups, libc::SIGHUP);
+        signal_groups(&groups, libc::SIGTERM);
+        // Stopped jobs only act on the signals once continued
+        signal_groups(&groups, libc::SIGCONT);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (master, pid);
+        if !already_exited {
+            let _ = killer.kill();
+        }
+    }
+
+    let started = Instant::now();
+    let mut forced = false;
+    loop {
+        let exited = exit.lock().unwrap().as_ref().map(|e| e.exit.clone());
+        #[cfg(unix)]
+        let alive = groups_alive(&groups);
+        #[cfg(not(unix))]
+        let alive = false;
+
+        if let (Some(exit), false) = (&exited, alive) {
+            return Ok(KillReport {
+                exit: exit.clone(),
+                forced,
+            });
+        }
+        let elapsed = started.elapsed();
+        if !forced && elapsed >= KILL_GRACE_PERIOD {
+            println!("[DEBUG] Terminal did not exit after {:?}, sending SIGKILL", KILL_GRACE_PERIOD);
+            #[cfg(unix)]
+            signal_groups(&groups, libc::SIGKILL);
+            if exited.is_none() {
+                let _ = killer.kill();
+            }
+            forced = true;
+        }
+        if elapsed >= KILL_GRACE_PERIOD + REAP_TIMEOUT {
+            return exited
+                .map(|exit| KillReport { exit, forced })
+                .ok_or_else(|| "Timed out waiting for terminal to exit".to_string());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Remove a terminal from the map and terminate its processes; the PTY is closed on return
+pub fn terminate_terminal(
+    terminals: &Mutex<HashMap<String, TerminalInstance>>,
+    id: &str,
+) -> Result<KillReport, String> {
+    let mut terminal = terminals
+        .lock()
+        .unwrap()
+        .remove(id)
+        .ok_or_else(|| format!("Terminal not found: {}", id))?;
+    let report = terminate(
+        &*terminal.pty_pair.master,
+        terminal.pid,
+        &mut *terminal.killer,
+        &terminal.exit,
+    )?;
+    println!("[DEBUG] Terminal {} terminated: {:?}", id, report);
+    Ok(report)
+}
+
 #[tauri::command]
-pub fn kill_terminal(state: State<'_, TerminalState>, id: String) -> Result<(), String> {
-    let mut terminals = state.terminals.lock().unwrap();
+pub async fn kill_terminal(state: State<'_, TerminalState>, id: String) -> Result<KillReport, String> {
+    let terminals = state.terminals.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let is_local = terminals.lock().unwrap().contains_key(&id);
+        if is_local {
+            terminate_terminal(&terminals, &id)
+        } else {
+            kill_session(&id)
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to kill terminal: {}", e))?
+}
 
-    if terminals.remove(&id).is_some() {
-        Ok(())
-    } else {
-        Err(format!("Terminal not found: {}", id))
+/// Attach to a running terminal: returns its buffered output for replay, after which the
+/// caller follows `terminal-output` events with a larger offset
+#[tauri::command]
+pub async fn attach_terminal(state: State<'_, TerminalState>, id: String) -> Result<TerminalSnapshot, String> {
+    let local = {
+        let terminals = state.terminals.lock().unwrap();
+        terminals.get(&id).map(|terminal| {
+            let scrollback = terminal.scrollback.lock().unwrap();
+            (scrollback.contents(), scrollback.end_offset())
+        })
+    };
+    match local {
+        Some((data, offset)) => Ok(TerminalSnapshot { id, data, offset }),
+        None => tauri::async_runtime::spawn_blocking(move || attach_session(&id))
+            .await
+            .map_err(|e| format!("Failed to attach terminal: {}", e))?,
     }
 }
 
+/// Commands run in a terminal so far, as reported by its shell integration
 #[tauri::command]
-pub fn list_terminals(state: State<'_, TerminalState>) -> Vec<String> {
+pub async fn get_terminal_history(state: State<'_, TerminalState>, id: String) -> Result<CommandHistory, String> {
+    let local = {
+        let terminals = state.terminals.lock().unwrap();
+        terminals.get(&id).map(|terminal| terminal.integration.lock().unwrap().history())
+    };
+    match local {
+        Some(history) => Ok(history),
+        None => tauri::async_runtime::spawn_blocking(move || session_history(&id))
+            .await
+            .map_err(|e| format!("Failed to read terminal history: {}", e))?,
+    }
+}
+
+/// What a terminal is showing right now, as plain text and optionally with each cell's style
+#[tauri::command]
+pub async fn get_terminal_screen(
+    state: State<'_, TerminalState>,
+    id: String,
+    styled: Option<bool>,
+) -> Result<ScreenSnapshot, String> {
+    let styled = styled.unwrap_or(false);
+    let local = {
+        let terminals = state.terminals.lock().unwrap();
+        terminals.get(&id).map(|terminal| terminal.screen.lock().unwrap().snapshot(styled))
+    };
+    match local {
+        Some(screen) => Ok(screen),
+        None => tauri::async_runtime::spawn_blocking(move || session_screen(&id, styled))
+            .await
+            .map_err(|e| format!("Failed to read terminal screen: {}", e))?,
+    }
+}
+
+/// Start recording a terminal into the project's recordings, replacing any recording in progress
+pub fn record_terminal(
+    terminals: &Mutex<HashMap<String, TerminalInstance>>,
+    id: &str,
+    project_path: &str,
+    options: RecordingOptions,
+) -> Result<String, String> {
+    let terminals = terminals.lock().unwrap();
+    let Some(terminal) = terminals.get(id) else {
+        drop(terminals);
+        if !is_session(id) {
+            return Err(format!("Terminal not found: {}", id));
+        }
+        return record_session(id, project_path, options);
+    };
+    // Holding the screen lock keeps output from slipping between the opening screen and the recorder
+    let screen = terminal.screen.lock().unwrap();
+    let recorder = Recorder::create(project_path, id, &options, &screen)?;
+    let path = recorder.path().to_string_lossy().to_string();
+    *terminal.recorder.lock().unwrap() = Some(recorder);
+    Ok(path)
+}
+
+/// Record a running terminal; returns the recording's path
+#[tauri::command]
+pub async fn start_recording(
+    state: State<'_, TerminalState>,
+    id: String,
+    project_path: String,
+    options: Option<RecordingOptions>,
+) -> Result<String, String> {
+    let terminals = state.terminals.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        record_terminal(&terminals, &id, &project_path, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Failed to start recording: {}", e))?
+}
+
+#[tauri::command]
+pub async fn stop_recording(state: State<'_, TerminalState>, id: String) -> Result<(), String> {
+    let local = {
+        let terminals = state.terminals.lock().unwrap();
+        terminals.get(&id).map(|terminal| terminal.recorder.lock().unwrap().take())
+    };
+    let Some(recorder) = local else {
+        return tauri::async_runtime::spawn_blocking(move || {
+            if !is_session(&id) {
+                return Err(format!("Terminal not found: {}", id));
+            }
+            stop_session_recording(&id)
+        })
+        .await
+        .map_err(|e| format!("Failed to stop recording: {}", e))?;
+    };
+    match recorder {
+        Some(recorder) => {
+            println!("[DEBUG] Stopped recording {}", recorder.path().display());
+            Ok(())
+        }
+        None => Err(format!("Terminal is not being recorded: {}", id)),
+    }
+}
+
+/// Running terminals, and exited ones whose tabs have not been closed yet
+#[tauri::command]
+pub fn list_terminals(state: State<'_, TerminalState>) -> Vec<TerminalInfo> {
     let terminals = state.terminals.lock().unwrap();
-    terminals.keys().cloned().collect()
+    let mut list: Vec<TerminalInfo> = terminals
+        .iter()
+        .map(|(id, terminal)| TerminalInfo {
+            id: id.clone(),
+            pid: terminal.pid,
+            started_at: terminal.started_at,
+            cwd: terminal.integration.lock().unwrap().cwd(),
+            exit: terminal.exit.lock().unwrap().clone(),
+        })
+        .collect();
+    list.sort_by_key(|t| t.started_at);
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_complete_text_immediately() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"hello "), "hello ");
+        assert_eq!(decoder.decode("wörld".as_bytes()), "wörld");
+        assert_eq!(decoder.finish(), "");
+    }
+
+    #[test]
+    fn holds_a_character_split_across_reads() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "aé".as_bytes();
+        assert_eq!(decoder.decode(&bytes[..2]), "a");
+        assert_eq!(decoder.decode(&bytes[2..]), "é");
+    }
+
+    #[test]
+    fn reassembles_four_byte_character_one_byte_at_a_time() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "🦀!".as_bytes();
+        let mut output = String::new();
+        for (i, byte) in bytes.iter().enumerate() {
+            let decoded = decoder.decode(&[*byte]);
+            if i < 3 {
+                assert_eq!(decoded, "", "byte {} should be held back", i);
+            }
+            output.push_str(&decoded);
+        }
+        assert_eq!(output, "🦀!");
+    }
+
+    #[test]
+    fn keeps_only_the_incomplete_tail_of_a_read() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "ab€€".as_bytes();
+        // Ends one byte into the second euro sign
+        assert_eq!(decoder.decode(&bytes[..6]), "ab€");
+        assert_eq!(decoder.decode(&bytes[6..]), "€");
+    }
+
+    #[test]
+    fn replaces_invalid_bytes_and_carries_on() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"f\xffg"), "f\u{FFFD}g");
+        // A sequence cut short by an ASCII byte is invalid, not incomplete
+        assert_eq!(decoder.decode(b"\xe2\x82"), "");
+        assert_eq!(decoder.decode(b"A"), "\u{FFFD}A");
+    }
+
+    #[test]
+    fn finish_flushes_an_incomplete_character() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"x\xf0\x9f"), "x");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+        assert_eq!(decoder.finish(), "");
+        assert_eq!(decoder.decode(b"y"), "y");
+    }
 }
@@ -0,0 +1,911 @@
+use crate::git::{create_milhouse_dir, run_git};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+/// Test run history, newest last
+const HISTORY_PATH: &str = ".milhouse/test-history.json";
+
+/// Runs kept in the history file
+const MAX_HISTORY_RUNS: usize = 20;
+
+/// Longest failure message kept per test
+const MAX_MESSAGE_CHARS: usize = 4000;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiscoveredTest {
+    /// `<framework>:<name>`, accepted by `run_tests` to run just this test
+    pub id: String,
+    pub framework: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TestLocation {
+    pub path: String,
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TestResult {
+    pub id: String,
+    pub framework: String,
+    pub name: String,
+    pub status: TestStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<TestLocation>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TestRunSummary {
+    pub id: String,
+    pub project_path: String,
+    /// "all", "one" or "failed"
+    pub scope: String,
+    pub frameworks: Vec<String>,
+    pub running: bool,
+    /// Seconds since the epoch
+    pub started_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Commit checked out when the run started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    pub counts: TestCounts,
+    /// Tests that passed in the previous run and fail in this one
+    pub newly_failing: Vec<String>,
+    /// Frameworks that could not be run or whose output could not be parsed
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TestRun {
+    #[serde(flatten)]
+    pub summary: TestRunSummary,
+    pub results: Vec<TestResult>,
+}
+
+/// Projects with a test run in progress
+#[derive(Default)]
+pub struct TestExplorerState {
+    running: Mutex<HashSet<String>>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn truncate(message: String) -> String {
+    if message.chars().count() > MAX_MESSAGE_CHARS {
+        message.chars().take(MAX_MESSAGE_CHARS).collect::<String>() + "…"
+    } else {
+        message
+    }
+}
+
+fn relative_path(root: &str, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| path.to_string())
+}
+
+fn run(root: &str, program: &str, args: &[String]) -> Result<Output, String> {
+    Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+fn file_contains(path: &Path, needle: &str) -> bool {
+    fs::read_to_string(path).map(|c| c.contains(needle)).unwrap_or(false)
+}
+
+/// Test frameworks used by the project
+fn detect_frameworks(root: &str) -> Vec<String> {
+    let root_path = Path::new(root);
+    let mut frameworks = Vec::new();
+    if root_path.join("Cargo.toml").exists() {
+        frameworks.push("cargo".to_string());
+    }
+    if let Ok(content) = fs::read_to_string(root_path.join("package.json")) {
+        let package: Value = serde_json::from_str(&content).unwrap_or_default();
+        let has_dependency = |name: &str| {
+            ["dependencies", "devDependencies"]
+                .iter()
+                .any(|section| package[section].get(name).is_some())
+        };
+        if has_dependency("vitest") {
+            frameworks.push("vitest".to_string());
+        } else if has_dependency("jest") {
+            frameworks.push("jest".to_string());
+        }
+    }
+    let uses_pytest = root_path.join("pytest.ini").exists()
+        || root_path.join("conftest.py").exists()
+        || file_contains(&root_path.join("pyproject.toml"), "pytest")
+        || file_contains(&root_path.join("setup.cfg"), "pytest")
+        || file_contains(&root_path.join("tox.ini"), "pytest");
+    if uses_pytest {
+        frameworks.push("pytest".to_string());
+    }
+    frameworks
+}
+
+fn discovered(framework: &str, name: String, file: Option<String>) -> DiscoveredTest {
+    DiscoveredTest {
+        id: format!("{}:{}", framework, name),
+        framework: framework.to_string(),
+        name,
+        file,
+    }
+}
+
+fn discover(root: &str, framework: &str) -> Result<Vec<DiscoveredTest>, String> {
+    let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    match framework {
+        "cargo" => {
+            let output = run(root, "cargo", &args(&["test", "--workspace", "--", "--list", "--format", "terse"]))?;
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.strip_suffix(": test"))
+                .map(|name| discovered("cargo", name.to_string(), None))
+                .collect())
+        }
+        "vitest" => {
+            let output = run(root, "npx", &args(&["--no-install", "vitest", "list", "--json"]))?;
+            let tests: Vec<Value> = serde_json::from_slice(&output.stdout)
+                .map_err(|e| format!("Failed to parse vitest test list: {}", e))?;
+            Ok(tests
+                .iter()
+                .filter_map(|t| {
+                    let file = relative_path(root, t["file"].as_str()?);
+                    // vitest joins suite names with " > "; -t matches them joined with spaces
+                    let name = t["name"].as_str()?.replace(" > ", " ");
+                    Some(discovered("vitest", format!("{}::{}", file, name), Some(file)))
+                })
+                .collect())
+        }
+        // jest can only list test files
+        "jest" => {
+            let output = run(root, "npx", &args(&["--no-install", "jest", "--listTests"]))?;
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| {
+                    let file = relative_path(root, line.trim());
+                    discovered("jest", file.clone(), Some(file))
+                })
+                .collect())
+        }
+        "pytest" => {
+            let output = run(root, "pytest", &args(&["--collect-only", "-q"]))?;
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .take_while(|line| !line.trim().is_empty())
+                .filter(|line| line.contains("::"))
+                .map(|line| {
+                    let file = line.split("::").next().map(|f| f.to_string());
+                    discovered("pytest", line.trim().to_string(), file)
+                })
+                .collect())
+        }
+        other => Err(format!("Unknown test framework: {}", other)),
+    }
+}
+
+/// `path:line[:column]` from the end of a string
+fn parse_location(text: &str) -> Option<TestLocation> {
+    let mut parts = text.trim().trim_end_matches(':').rsplitn(3, ':');
+    let last = parts.next()?;
+    let middle = parts.next()?;
+    let (path, line) = match (middle.parse::<usize>(), parts.next()) {
+        // path:line:column
+        (Ok(line), Some(path)) if last.parse::<usize>().is_ok() => (path, line),
+        // path:line
+        _ => (middle, last.parse::<usize>().ok()?),
+    };
+    Some(TestLocation {
+        path: path.to_string(),
+        line,
+    })
+}
+
+fn result(framework: &str, name: &str, status: TestStatus) -> TestResult {
+    TestResult {
+        id: format!("{}:{}", framework, name),
+        framework: framework.to_string(),
+        name: name.to_string(),
+        status,
+        duration_ms: None,
+        message: None,
+        location: None,
+    }
+}
+
+/// Parse libtest's human-readable output, including the `---- name stdout ----` failure sections
+fn parse_cargo_output(output: &str) -> Vec<TestResult> {
+    let mut results: Vec<TestResult> = Vec::new();
+    for line in output.lines() {
+        let Some((name, outcome)) = line.strip_prefix("test ").and_then(|l| l.rsplit_once(" ... ")) else {
+            continue;
+        };
+        let status = match outcome.trim() {
+            "ok" => TestStatus::Passed,
+            "FAILED" => TestStatus::Failed,
+            o if o.starts_with("ignored") => TestStatus::Skipped,
+            _ => continue,
+        };
+        results.push(result("cargo", name, status));
+    }
+
+    let mut current: Option<(String, Vec<&str>)> = None;
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("---- ").and_then(|l| l.strip_suffix(" stdout ----")) {
+            sections.extend(current.take());
+            current = Some((name.to_string(), Vec::new()));
+        } else if line == "failures:" || line.starts_with("test result:") {
+            sections.extend(current.take());
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    sections.extend(current);
+
+    for (name, lines) in sections {
+        let Some(test) = results.iter_mut().find(|r| r.name == name) else {
+            continue;
+        };
+        let mut message_lines = Vec::new();
+        // Backtraces are noise in a failure message
+        for line in lines.into_iter().take_while(|l| *l != "stack backtrace:") {
+            if let Some(at) = line.split_once("panicked at ").map(|(_, rest)| rest) {
+                // Old format: panicked at 'message', src/lib.rs:10:5
+                let location = match at.strip_prefix('\'').and_then(|m| m.rsplit_once("', ")) {
+                    Some((message, location)) => {
+                        message_lines.push(message);
+                        location
+                    }
+                    None => at,
+                };
+                test.location = parse_location(location);
+            } else if !line.starts_with("note: run with `RUST_BACKTRACE") {
+                message_lines.push(line);
+            }
+        }
+        let message = message_lines.join("\n").trim().to_string();
+        if !message.is_empty() {
+            test.message = Some(truncate(message));
+        }
+    }
+    results
+}
+
+/// Parse the Jest-compatible JSON report written by both jest and vitest
+fn parse_js_report(root: &str, framework: &str, report: &str) -> Result<Vec<TestResult>, String> {
+    let report: Value =
+        serde_json::from_str(report).map_err(|e| format!("Failed to parse {} report: {}", framework, e))?;
+    let mut results = Vec::new();
+    for file in report["testResults"].as_array().into_iter().flatten() {
+        let path = relative_path(root, file["name"].as_str().unwrap_or(""));
+        let assertions = file["assertionResults"].as_array().cloned().unwrap_or_default();
+
+        // A file that failed to load has no assertions, only a message
+        if assertions.is_empty() {
+            if file["status"] == "failed" {
+                let mut failed = result(framework, &path, TestStatus::Failed);
+                failed.message = file["message"].as_str().map(|m| truncate(m.to_string()));
+                results.push(failed);
+            }
+            continue;
+        }
+
+        for assertion in assertions {
+            let full_name = assertion["fullName"].as_str().unwrap_or("");
+            let status = match assertion["status"].as_str().unwrap_or("") {
+                "passed" => TestStatus::Passed,
+                "failed" => TestStatus::Failed,
+                _ => TestStatus::Skipped,
+            };
+            let mut test = result(framework, &format!("{}::{}", path, full_name), status);
+            test.duration_ms = assertion["duration"].as_f64().map(|d| d as u64);
+            let messages: Vec<&str> = assertion["failureMessages"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str())
+                .collect();
+            if !messages.is_empty() {
+                test.message = Some(truncate(messages.join("\n")));
+            }
+            test.location = match assertion["location"]["line"].as_u64() {
+                Some(line) => Some(TestLocation {
+                    path: path.clone(),
+                    line: line as usize,
+                }),
+                // Fall back to the first stack frame in the test file
+                None => messages.iter().flat_map(|m| m.lines()).find_map(|line| {
+                    let start = line.find(&path)?;
+                    let frame = line[start..].trim_end_matches(')');
+                    parse_location(frame)
+                }),
+            };
+            results.push(test);
+        }
+    }
+    Ok(results)
+}
+
+/// Parse `pytest -v -rfE --tb=short` output
+fn parse_pytest_output(output: &str) -> Vec<TestResult> {
+    let mut results: Vec<TestResult> = Vec::new();
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(node_id), Some(outcome)) = (words.next(), words.next()) else {
+            continue;
+        };
+        if !node_id.contains("::") {
+            continue;
+        }
+        let status = match outcome {
+            "PASSED" | "XPASS" => TestStatus::Passed,
+            "FAILED" | "ERROR" => TestStatus::Failed,
+            "SKIPPED" | "XFAIL" => TestStatus::Skipped,
+            _ => continue,
+        };
+        // Setup errors repeat a test that was already reported
+        match results.iter_mut().find(|r| r.name == node_id) {
+            Some(existing) if status == TestStatus::Failed => existing.status = status,
+            Some(_) => {}
+            None => results.push(result("pytest", node_id, status)),
+        }
+    }
+
+    // Failure sections look like `____ TestClass.test_name ____` followed by a short traceback
+    let mut section: Option<(String, Vec<&str>)> = None;
+    let mut sections = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("___") && trimmed.ends_with("___") {
+            sections.extend(section.take());
+            let title = trimmed.trim_matches('_').trim();
+            let title = title
+                .strip_prefix("ERROR at setup of ")
+                .or_else(|| title.strip_prefix("ERROR at teardown of "))
+                .unwrap_or(title);
+            section = Some((title.to_string(), Vec::new()));
+        } else if trimmed.starts_with("===") {
+            sections.extend(section.take());
+        } else if let Some((_, lines)) = section.as_mut() {
+            lines.push(line);
+        }
+    }
+    sections.extend(section);
+
+    for (title, lines) in sections {
+        let Some(test) = results.iter_mut().find(|r| {
+            r.status == TestStatus::Failed
+                && r.name.split_once("::").map(|(_, rest)| rest.replace("::", ".")) == Some(title.clone())
+        }) else {
+            continue;
+        };
+        let file = test.name.split("::").next().unwrap_or("").to_string();
+        test.location = lines
+            .iter()
+            .filter_map(|line| line.split_once(": ").and_then(|(location, _)| parse_location(location)))
+            .filter(|location| location.path.ends_with(".py"))
+            .max_by_key(|location| location.path == file);
+        let errors: Vec<&str> = lines.iter().filter_map(|line| line.strip_prefix("E ")).map(|l| l.trim()).collect();
+        if !errors.is_empty() {
+            test.message = Some(truncate(errors.join("\n")));
+        }
+    }
+
+    // Short summary lines carry a one-line message for failures without a section
+    for line in output.lines() {
+        let Some(rest) = line.strip_prefix("FAILED ").or_else(|| line.strip_prefix("ERROR ")) else {
+            continue;
+        };
+        let Some((node_id, message)) = rest.split_once(" - ") else {
+            continue;
+        };
+        if let Some(test) = results.iter_mut().find(|r| r.name == node_id && r.message.is_none()) {
+            test.message = Some(truncate(message.to_string()));
+        }
+    }
+    results
+}
+
+/// Regex matching exactly one of `names`, for jest/vitest `-t`
+fn name_pattern(names: &[&str]) -> String {
+    let escaped: Vec<String> = names
+        .iter()
+        .map(|name| {
+            name.chars()
+                .map(|c| if "\\^$.|?*+()[]{}".contains(c) { format!("\\{}", c) } else { c.to_string() })
+                .collect()
+        })
+        .collect();
+    format!("^({})$", escaped.join("|"))
+}
+
+/// Run one framework's tests, restricted to `names` when given
+fn run_framework(root: &str, framework: &str, names: Option<&[String]>) -> Result<Vec<TestResult>, String> {
+    match framework {
+        "cargo" => {
+            let mut args: Vec<String> = ["test", "--workspace", "--no-fail-fast", "--"].iter().map(|a| a.to_string()).collect();
+            if let Some(names) = names {
+                args.push("--exact".to_string());
+                args.extend(names.iter().cloned());
+            }
+            let output = run(root, "cargo", &args)?;
+            let results = parse_cargo_output(&String::from_utf8_lossy(&output.stdout));
+            if results.is_empty() && !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("cargo test failed: {}", stderr.lines().last().unwrap_or("")));
+            }
+            Ok(results)
+        }
+        "vitest" | "jest" => {
+            let report_path = std::env::temp_dir().join(format!("milhouse-tests-{}.json", Uuid::new_v4().simple()));
+            let mut args = vec!["--no-install".to_string(), framework.to_string()];
+            if framework == "vitest" {
+                args.extend(["run".to_string(), "--reporter=json".to_string()]);
+            } else {
+                args.extend(["--json".to_string(), "--testLocationInResults".to_string()]);
+            }
+            args.push(format!("--outputFile={}", report_path.display()));
+            if let Some(names) = names {
+                // Ids are `file::full name`, or just `file` for whole files
+                let mut files: Vec<&str> = Vec::new();
+                let mut tests: Vec<&str> = Vec::new();
+                for name in names {
+                    let (file, test) = match name.split_once("::") {
+                        Some((file, test)) => (file, Some(test)),
+                        None => (name.as_str(), None),
+                    };
+                    if !files.contains(&file) {
+                        files.push(file);
+                    }
+                    tests.extend(test);
+                }
+                args.extend(files.iter().map(|f| f.to_string()));
+                if !tests.is_empty() {
+                    args.push("-t".to_string());
+                    args.push(name_pattern(&tests));
+                }
+            }
+            let output = run(root, "npx", &args)?;
+            let report = fs::read_to_string(&report_path);
+            let _ = fs::remove_file(&report_path);
+            match report {
+                Ok(report) => parse_js_report(root, framework, &report),
+                Err(_) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    Err(format!("{} produced no report: {}", framework, stderr.lines().last().unwrap_or("")))
+                }
+            }
+        }
+        "pytest" => {
+            let mut args: Vec<String> = ["-v", "-rfE", "--tb=short"].iter().map(|a| a.to_string()).collect();
+            if let Some(names) = names {
+                args.extend(names.iter().cloned());
+            }
+            let output = run(root, "pytest", &args)?;
+            let results = parse_pytest_output(&String::from_utf8_lossy(&output.stdout));
+            // Exit code 5 means no tests were collected
+            if results.is_empty() && !matches!(output.status.code(), Some(0) | Some(5)) {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                return Err(format!("pytest failed: {}", stdout.lines().last().unwrap_or("")));
+            }
+            Ok(results)
+        }
+        other => Err(format!("Unknown test framework: {}", other)),
+    }
+}
+
+fn load_history(root: &str) -> Result<Vec<TestRun>, String> {
+    let path = Path::new(root).join(HISTORY_PATH);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read test history: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse test history: {}", e))
+}
+
+fn save_history(root: &str, runs: &[TestRun]) -> Result<(), String> {
+    let path = Path::new(root).join(HISTORY_PATH);
+    if let Some(dir) = path.parent() {
+        create_milhouse_dir(root, dir)?;
+    }
+    let content = serde_json::to_string(runs).map_err(|e| format!("Failed to serialize test history: {}", e))?;
+    // Write then rename so a crash never leaves a half-written history behind
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write test history: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write test history: {}", e))
+}
+
+/// A framework to run, and which of its tests (`None` means all)
+type PlannedRun = (String, Option<Vec<String>>);
+
+/// Which frameworks and tests a run covers
+fn plan_run(
+    root: &str,
+    scope: &str,
+    test_id: Option<&str>,
+    framework: Option<&str>,
+) -> Result<Vec<PlannedRun>, String> {
+    match scope {
+        "all" => {
+            let frameworks = match framework {
+                Some(framework) => vec![framework.to_string()],
+                None => detect_frameworks(root),
+            };
+            if frameworks.is_empty() {
+                return Err("No test framework detected".to_string());
+            }
+            Ok(frameworks.into_iter().map(|f| (f, None)).collect())
+        }
+        "one" => {
+            let id = test_id.ok_or_else(|| "A test id is required".to_string())?;
+            let (framework, name) = id.split_once(':').ok_or_else(|| format!("Invalid test id: {}", id))?;
+            Ok(vec![(framework.to_string(), Some(vec![name.to_string()]))])
+        }
+        "failed" => {
+            let history = load_history(root)?;
+            let last = history
+                .iter()
+                .rev()
+                .find(|r| !r.summary.running)
+                .ok_or_else(|| "No previous test run".to_string())?;
+            let mut failed: HashMap<String, Vec<String>> = HashMap::new();
+            for test in last.results.iter().filter(|t| t.status == TestStatus::Failed) {
+                if framework.map(|f| f == test.framework).unwrap_or(true) {
+                    failed.entry(test.framework.clone()).or_default().push(test.name.clone());
+                }
+            }
+            if failed.is_empty() {
+                return Err("No failed tests in the previous run".to_string());
+            }
+            Ok(failed.into_iter().map(|(f, names)| (f, Some(names))).collect())
+        }
+        other => Err(format!("Unknown test scope: {}", other)),
+    }
+}
+
+/// Detect the project's frameworks and list their tests
+#[tauri::command]
+pub async fn discover_tests(project_path: String) -> Result<Vec<DiscoveredTest>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut tests = Vec::new();
+        for framework in detect_frameworks(&project_path) {
+            match discover(&project_path, &framework) {
+                Ok(found) => tests.extend(found),
+                Err(e) => println!("[DEBUG] Failed to discover {} tests: {}", framework, e),
+            }
+        }
+        tests
+    })
+    .await
+    .map_err(|e| format!("Test discovery failed: {}", e))
+}
+
+/// Marks a project as no longer running tests when dropped, even if the run panicked
+struct RunningProject {
+    app: AppHandle,
+    project_path: String,
+}
+
+impl Drop for RunningProject {
+    fn drop(&mut self) {
+        let state = self.app.state::<TestExplorerState>();
+        state.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.project_path);
+    }
+}
+
+/// Start a test run in the background. `scope` is "all", "one" (with `test_id`) or "failed",
+/// which re-runs the failures from the previous run. Emits `test-run-updated` when it starts
+/// and finishes.
+#[tauri::command]
+pub fn run_tests(
+    app: AppHandle,
+    state: State<'_, TestExplorerState>,
+    project_path: String,
+    scope: String,
+    test_id: Option<String>,
+    framework: Option<String>,
+) -> Result<TestRunSummary, String> {
+    let plan = plan_run(&project_path, &scope, test_id.as_deref(), framework.as_deref())?;
+    if !state.running.lock().unwrap().insert(project_path.clone()) {
+        return Err("Tests are already running for this project".to_string());
+    }
+
+    let summary = TestRunSummary {
+        id: Uuid::new_v4().simple().to_string()[..8].to_string(),
+        project_path: project_path.clone(),
+        scope,
+        frameworks: plan.iter().map(|(f, _)| f.clone()).collect(),
+        running: true,
+        started_at: now_secs(),
+        duration_ms: None,
+        head: run_git(&project_path, &["rev-parse", "HEAD"]).ok().map(|h| h.trim().to_string()),
+        counts: TestCounts::default(),
+        newly_failing: vec![],
+        errors: vec![],
+    };
+    let _ = app.emit("test-run-updated", summary.clone());
+
+    let mut finished = summary.clone();
+    thread::spawn(move || {
+        let running = RunningProject {
+            app: app.clone(),
+            project_path: finished.project_path.clone(),
+        };
+        let started = Instant::now();
+        let mut results = Vec::new();
+        for (framework, names) in plan {
+            match run_framework(&finished.project_path, &framework, names.as_deref()) {
+                Ok(found) => results.extend(found),
+                Err(e) => finished.errors.push(e),
+            }
+        }
+
+        let mut history = load_history(&finished.project_path).unwrap_or_default();
+        // Latest known status of every test, so partial runs don't hide earlier results
+        let previous: HashMap<&str, &TestStatus> = history
+            .iter()
+            .flat_map(|run| run.results.iter().map(|t| (t.id.as_str(), &t.status)))
+            .collect();
+        finished.newly_failing = results
+            .iter()
+            .filter(|t| t.status == TestStatus::Failed && previous.get(t.id.as_str()) == Some(&&TestStatus::Passed))
+            .map(|t| t.id.clone())
+            .collect();
+        finished.counts = TestCounts {
+            passed: results.iter().filter(|t| t.status == TestStatus::Passed).count(),
+            failed: results.iter().filter(|t| t.status == TestStatus::Failed).count(),
+            skipped: results.iter().filter(|t| t.status == TestStatus::Skipped).count(),
+        };
+        finished.running = false;
+        finished.duration_ms = Some(started.elapsed().as_millis() as u64);
+        println!(
+            "[DEBUG] Test run {} finished: {} passed, {} failed, {} skipped",
+            finished.id, finished.counts.passed, finished.counts.failed, finished.counts.skipped
+        );
+
+        history.push(TestRun {
+            summary: finished.clone(),
+            results,
+        });
+        let excess = history.len().saturating_sub(MAX_HISTORY_RUNS);
+        history.drain(..excess);
+        if let Err(e) = save_history(&finished.project_path, &history) {
+            println!("[DEBUG] {}", e);
+        }
+
+        drop(running);
+        let _ = app.emit("test-run-updated", finished);
+    });
+
+    Ok(summary)
+}
+
+/// Past test runs, newest first, without per-test results
+#[tauri::command]
+pub fn list_test_runs(project_path: String) -> Result<Vec<TestRunSummary>, String> {
+    Ok(load_history(&project_path)?
+        .into_iter()
+        .rev()
+        .map(|run| run.summary)
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_test_run(project_path: String, run_id: String) -> Result<TestRun, String> {
+    load_history(&project_path)?
+        .into_iter()
+        .find(|run| run.summary.id == run_id)
+        .ok_or_else(|| format!("Test run not found: {}", run_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(results: &'a [TestResult], name: &str) -> &'a TestResult {
+        results.iter().find(|r| r.name == name).unwrap()
+    }
+
+    #[test]
+    fn parses_cargo_statuses_and_failure_sections() {
+        let output = "\
+running 3 tests
+test tests::adds ... ok
+test tests::slow ... ignored, takes a minute
+test tests::subtracts ... FAILED
+
+failures:
+
+---- tests::subtracts stdout ----
+
+thread 'tests::subtracts' panicked at src/math.rs:10:5:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::subtracts
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        let results = parse_cargo_output(output);
+        assert_eq!(results.len(), 3);
+        assert_eq!(find(&results, "tests::adds").status, TestStatus::Passed);
+        assert_eq!(find(&results, "tests::slow").status, TestStatus::Skipped);
+
+        let failed = find(&results, "tests::subtracts");
+        assert_eq!(failed.status, TestStatus::Failed);
+        assert_eq!(failed.message.as_deref(), Some("assertion `left == right` failed\n  left: 1\n right: 2"));
+        let location = failed.location.as_ref().unwrap();
+        assert_eq!((location.path.as_str(), location.line), ("src/math.rs", 10));
+    }
+
+    #[test]
+    fn parses_old_cargo_panic_format_and_drops_backtraces() {
+        let output = "\
+test parse ... FAILED
+
+failures:
+
+---- parse stdout ----
+thread 'parse' panicked at 'boom', src/lib.rs:3:9
+stack backtrace:
+   0: rust_begin_unwind
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        let results = parse_cargo_output(output);
+        let failed = find(&results, "parse");
+        assert_eq!(failed.message.as_deref(), Some("boom"));
+        let location = failed.location.as_ref().unwrap();
+        assert_eq!((location.path.as_str(), location.line), ("src/lib.rs", 3));
+    }
+
+    #[test]
+    fn parses_js_report_assertions_and_load_failures() {
+        let report = r#"{
+            "testResults": [
+                {
+                    "name": "/proj/src/math.test.ts",
+                    "status": "failed",
+                    "assertionResults": [
+                        {"fullName": "math adds", "status": "passed", "duration": 4.7, "location": {"line": 3, "column": 1}},
+                        {
+                            "fullName": "math subtracts",
+                            "status": "failed",
+                            "failureMessages": ["Error: expected 1 to be 2\n    at Object.<anonymous> (/proj/src/math.test.ts:12:7)"]
+                        },
+                        {"fullName": "math divides", "status": "pending"}
+                    ]
+                },
+                {
+                    "name": "/proj/src/broken.test.ts",
+                    "status": "failed",
+                    "message": "SyntaxError: Unexpected token",
+                    "assertionResults": []
+                }
+            ]
+        }"#;
+        let results = parse_js_report("/proj", "vitest", report).unwrap();
+        assert_eq!(results.len(), 4);
+
+        let passed = find(&results, "src/math.test.ts::math adds");
+        assert_eq!(passed.id, "vitest:src/math.test.ts::math adds");
+        assert_eq!(passed.status, TestStatus::Passed);
+        assert_eq!(passed.duration_ms, Some(4));
+        assert_eq!(passed.location.as_ref().map(|l| l.line), Some(3));
+
+        // Without a reported location the first stack frame in the test file is used
+        let failed = find(&results, "src/math.test.ts::math subtracts");
+        assert_eq!(failed.status, TestStatus::Failed);
+        assert!(failed.message.as_deref().unwrap().starts_with("Error: expected 1 to be 2"));
+        let location = failed.location.as_ref().unwrap();
+        assert_eq!((location.path.as_str(), location.line), ("src/math.test.ts", 12));
+
+        assert_eq!(find(&results, "src/math.test.ts::math divides").status, TestStatus::Skipped);
+
+        let broken = find(&results, "src/broken.test.ts");
+        assert_eq!(broken.status, TestStatus::Failed);
+        assert_eq!(broken.message.as_deref(), Some("SyntaxError: Unexpected token"));
+    }
+
+    #[test]
+    fn rejects_malformed_js_report() {
+        assert!(parse_js_report("/proj", "jest", "PASS src/a.test.ts").is_err());
+    }
+
+    #[test]
+    fn parses_pytest_verbose_output_and_tracebacks() {
+        let output = "\
+tests/test_math.py::test_adds PASSED                                     [ 25%]
+tests/test_math.py::TestMath::test_subtracts FAILED                      [ 50%]
+tests/test_math.py::test_slow SKIPPED (needs network)                    [ 75%]
+tests/test_db.py::test_query ERROR                                       [100%]
+
+==================================== ERRORS ====================================
+______________________ ERROR at setup of test_query ______________________
+file tests/test_db.py, line 5
+=================================== FAILURES ===================================
+__________________________ TestMath.test_subtracts __________________________
+tests/test_math.py:12: in test_subtracts
+    assert subtract(2, 1) == 2
+E   assert 1 == 2
+E    +  where 1 = subtract(2, 1)
+=========================== short test summary info ============================
+FAILED tests/test_math.py::TestMath::test_subtracts - assert 1 == 2
+ERROR tests/test_db.py::test_query - fixture 'db' not found
+";
+        let results = parse_pytest_output(output);
+        assert_eq!(results.len(), 4);
+        assert_eq!(find(&results, "tests/test_math.py::test_adds").status, TestStatus::Passed);
+        assert_eq!(find(&results, "tests/test_math.py::test_slow").status, TestStatus::Skipped);
+
+        let failed = find(&results, "tests/test_math.py::TestMath::test_subtracts");
+        assert_eq!(failed.status, TestStatus::Failed);
+        assert_eq!(failed.message.as_deref(), Some("assert 1 == 2\n+  where 1 = subtract(2, 1)"));
+        let location = failed.location.as_ref().unwrap();
+        assert_eq!((location.path.as_str(), location.line), ("tests/test_math.py", 12));
+
+        // A setup error without an `E` line falls back to the short summary message
+        let errored = find(&results, "tests/test_db.py::test_query");
+        assert_eq!(errored.status, TestStatus::Failed);
+        assert_eq!(errored.message.as_deref(), Some("fixture 'db' not found"));
+    }
+
+    #[test]
+    fn setup_error_after_pass_marks_pytest_test_failed() {
+        let output = "\
+tests/test_a.py::test_ok PASSED
+tests/test_a.py::test_ok ERROR
+";
+        let results = parse_pytest_output(output);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, TestStatus::Failed);
+    }
+}
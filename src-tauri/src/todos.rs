@@ -0,0 +1,457 @@
+use crate::drawer::TaskEntry;
+use crate::git::create_milhouse_dir;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+/// Per-project marker configuration
+const CONFIG_PATH: &str = ".milhouse/todos.json";
+
+/// TODOs imported into the task drawer
+const IMPORTED_PATH: &str = ".milhouse/todo-tasks.json";
+
+const DEFAULT_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Files larger than this are not scanned
+const MAX_SCAN_BYTES: u64 = 1024 * 1024;
+
+/// Tokens that start a comment; a marker only counts when one precedes it on the line
+const COMMENT_TOKENS: &[&str] = &["//", "#", "/*", "*", "--", "<!--", ";"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct TodoConfig {
+    markers: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TodoItem {
+    /// Stable across rescans as long as the comment text is unchanged, even if it moves
+    pub id: String,
+    /// Project-relative path with forward slashes
+    pub path: String,
+    /// 1-based
+    pub line: usize,
+    pub marker: String,
+    pub text: String,
+    /// Drawer task created from this item, if it was imported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TodosUpdated {
+    pub project_path: String,
+    pub total: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ImportedTodo {
+    todo_id: String,
+    task: TaskEntry,
+}
+
+struct ProjectTodos {
+    markers: Vec<String>,
+    files: HashMap<String, Vec<TodoItem>>,
+}
+
+/// Scan results, keyed by canonical project root
+#[derive(Default)]
+pub struct TodoState {
+    projects: Mutex<HashMap<String, ProjectTodos>>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn canonical_root(project_path: &str) -> Result<String, String> {
+    Path::new(project_path)
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to resolve project path: {}", e))
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+fn load_markers(root: &str) -> Vec<String> {
+    fs::read_to_string(Path::new(root).join(CONFIG_PATH))
+        .ok()
+        .and_then(|content| serde_json::from_str::<TodoConfig>(&content).ok())
+        .map(|config| config.markers)
+        .filter(|markers| !markers.is_empty())
+        .unwrap_or_else(|| DEFAULT_MARKERS.iter().map(|m| m.to_string()).collect())
+}
+
+fn load_imported(root: &str) -> Vec<ImportedTodo> {
+    fs::read_to_string(Path::new(root).join(IMPORTED_PATH))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_imported(root: &str, imported: &[ImportedTodo]) -> Result<(), String> {
+    let path = Path::new(root).join(IMPORTED_PATH);
+    if let Some(dir) = path.parent() {
+        create_milhouse_dir(root, dir)?;
+    }
+    let content =
+        serde_json::to_string_pretty(imported).map_err(|e| format!("Failed to serialize imported TODOs: {}", e))?;
+    // Write then rename so a crash never leaves a half-written file behind
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write imported TODOs: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write imported TODOs: {}", e))
+}
+
+/// Find `marker` as a whole word inside a comment on `line`, returning the text after it
+fn match_marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let mut search_from = 0;
+    while let Some(offset) = line[search_from..].find(marker) {
+        let start = search_from + offset;
+        let end = start + marker.len();
+        search_from = end;
+
+        let before = &line[..start];
+        // Markers quoted in backticks are being talked about, not used
+        let word_start = !before.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '`');
+        let word_end = !line[end..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+        if !word_start || !word_end || !COMMENT_TOKENS.iter().any(|t| before.contains(t)) {
+            continue;
+        }
+        // `TODO:` and `TODO(alice)` anywhere in a comment, or a marker opening the comment;
+        // this skips prose that merely mentions the word
+        let tagged = line[end..].starts_with([':', '(']);
+        let leading = COMMENT_TOKENS.iter().any(|t| before.trim_end().ends_with(t));
+        if !tagged && !leading {
+            continue;
+        }
+
+        // Skip an optional `(author)` and separator, e.g. `TODO(alice): text`
+        let mut rest = &line[end..];
+        if rest.starts_with('(') {
+            if let Some(close) = rest.find(')') {
+                rest = &rest[close + 1..];
+            }
+        }
+        let text = rest
+            .trim_start_matches([':', '-', ' ', '\t'])
+            .trim_end()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim_end();
+        return Some(text);
+    }
+    None
+}
+
+fn scan_source(relative: &str, source: &str, markers: &[String]) -> Vec<TodoItem> {
+    let mut items = Vec::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for (index, line) in source.lines().enumerate() {
+        let Some((marker, text)) = markers
+            .iter()
+            .find_map(|marker| match_marker(line, marker).map(|text| (marker, text)))
+        else {
+            continue;
+        };
+
+        // Identical comments in one file are told apart by their order
+        let key = format!("{}\0{}\0{}", relative, marker, text);
+        let occurrence = occurrences.entry(key.clone()).or_insert(0);
+        *occurrence += 1;
+        let hash = Sha256::digest(format!("{}\0{}", key, occurrence).as_bytes());
+        let id: String = hash.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+
+        items.push(TodoItem {
+            id,
+            path: relative.to_string(),
+            line: index + 1,
+            marker: marker.clone(),
+            text: text.to_string(),
+            task_id: None,
+        });
+    }
+    items
+}
+
+fn scan_file(root: &Path, path: &Path, markers: &[String]) -> Option<(String, Vec<TodoItem>)> {
+    let relative = relative_path(root, path)?;
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_SCAN_BYTES {
+        return None;
+    }
+    // Binary and non-UTF-8 files are skipped
+    let source = fs::read_to_string(path).ok()?;
+    Some((relative.clone(), scan_source(&relative, &source, markers)))
+}
+
+fn scan_project(root: &str) -> ProjectTodos {
+    let markers = load_markers(root);
+    let root_path = Path::new(root);
+    let mut files = HashMap::new();
+    for entry in WalkBuilder::new(root_path).build().flatten() {
+        if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        if let Some((relative, items)) = scan_file(root_path, entry.path(), &markers) {
+            if !items.is_empty() {
+                files.insert(relative, items);
+            }
+        }
+    }
+    ProjectTodos { markers, files }
+}
+
+/// All items for a project, sorted by path and line, with imported ones linked to their task
+fn collect_items(root: &str, project: &ProjectTodos) -> Vec<TodoItem> {
+    let imported: HashMap<String, String> = load_imported(root)
+        .into_iter()
+        .map(|i| (i.todo_id, i.task.id))
+        .collect();
+    let mut items: Vec<TodoItem> = project
+        .files
+        .values()
+        .flatten()
+        .cloned()
+        .map(|mut item| {
+            item.task_id = imported.get(&item.id).cloned();
+            item
+        })
+        .collect();
+    items.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    items
+}
+
+fn ensure_scanned(state: &TodoState, root: &str) {
+    let scanned = state.projects.lock().unwrap().contains_key(root);
+    if !scanned {
+        let project = scan_project(root);
+        state.projects.lock().unwrap().insert(root.to_string(), project);
+    }
+}
+
+fn same_items(a: &[TodoItem], b: &[TodoItem]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.id == y.id && x.line == y.line)
+}
+
+/// Rescan files reported by the watcher; projects that were never scanned are ignored
+pub fn update_paths(app: &AppHandle, root: &str, paths: &[PathBuf]) {
+    let total = {
+        let state = app.state::<TodoState>();
+        let mut projects = state.projects.lock().unwrap();
+        let Some(project) = projects.get_mut(root) else {
+            return;
+        };
+
+        let root_path = Path::new(root);
+        let mut changed = false;
+        for path in paths {
+            let Some(relative) = relative_path(root_path, path) else {
+                continue;
+            };
+            let items = scan_file(root_path, path, &project.markers)
+                .map(|(_, items)| items)
+                .unwrap_or_default();
+            let previous = if items.is_empty() {
+                project.files.remove(&relative)
+            } else {
+                project.files.insert(relative, items.clone())
+            };
+            changed |= !previous.map(|p| same_items(&p, &items)).unwrap_or(items.is_empty());
+        }
+        if !changed {
+            return;
+        }
+        project.files.values().map(|items| items.len()).sum()
+    };
+
+    let _ = app.emit(
+        "todos-updated",
+        TodosUpdated {
+            project_path: root.to_string(),
+            total,
+        },
+    );
+}
+
+/// Drawer tasks imported from TODO comments, for `list_tasks`
+pub fn imported_tasks(project_path: &str) -> Vec<TaskEntry> {
+    load_imported(project_path).into_iter().map(|i| i.task).collect()
+}
+
+/// Update the status of an imported task; returns false when `task_id` is not one of ours
+pub fn update_imported_status(project_path: &str, task_id: &str, status: &str) -> Result<bool, String> {
+    let mut imported = load_imported(project_path);
+    let Some(entry) = imported.iter_mut().find(|i| i.task.id == task_id) else {
+        return Ok(false);
+    };
+    entry.task.status = status.to_string();
+    save_imported(project_path, &imported)?;
+    Ok(true)
+}
+
+/// Full rescan of the project, e.g. after changing markers
+#[tauri::command]
+pub async fn scan_todos(app: AppHandle, project_path: String) -> Result<Vec<TodoItem>, String> {
+    let root = canonical_root(&project_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let project = scan_project(&root);
+        let items = collect_items(&root, &project);
+        println!("[DEBUG] Found {} TODO comments in {}", items.len(), root);
+        app.state::<TodoState>().projects.lock().unwrap().insert(root, project);
+        items
+    })
+    .await
+    .map_err(|e| format!("TODO scan failed: {}", e))
+}
+
+/// Marker comments in the project, scanning it on first use
+#[tauri::command]
+pub async fn list_todos(app: AppHandle, project_path: String) -> Result<Vec<TodoItem>, String> {
+    let root = canonical_root(&project_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<TodoState>();
+        ensure_scanned(&state, &root);
+        let projects = state.projects.lock().unwrap();
+        let project = projects.get(&root).ok_or_else(|| "Project is not scanned".to_string())?;
+        Ok(collect_items(&root, project))
+    })
+    .await
+    .map_err(|e| format!("TODO scan failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn get_todo_markers(project_path: String) -> Result<Vec<String>, String> {
+    Ok(load_markers(&project_path))
+}
+
+/// Save the project's markers to `.milhouse/todos.json` and rescan
+#[tauri::command]
+pub async fn set_todo_markers(
+    app: AppHandle,
+    project_path: String,
+    markers: Vec<String>,
+) -> Result<Vec<TodoItem>, String> {
+    let markers: Vec<String> = markers
+        .into_iter()
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    let path = Path::new(&project_path).join(CONFIG_PATH);
+    if let Some(dir) = path.parent() {
+        create_milhouse_dir(&project_path, dir)?;
+    }
+    let content = serde_json::to_string_pretty(&TodoConfig { markers })
+        .map_err(|e| format!("Failed to serialize TODO config: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write TODO config: {}", e))?;
+    scan_todos(app, project_path).await
+}
+
+/// Create drawer tasks for the selected TODO items. Items imported before are not duplicated;
+/// their existing task is returned instead.
+#[tauri::command]
+pub async fn import_todos(app: AppHandle, project_path: String, ids: Vec<String>) -> Result<Vec<TaskEntry>, String> {
+    let root = canonical_root(&project_path)?;
+    tauri::async_runtime::spawn_blocking(move || import_items(&app.state::<TodoState>(), root, &ids))
+        .await
+        .map_err(|e| format!("TODO import failed: {}", e))?
+}
+
+fn import_items(state: &TodoState, root: String, ids: &[String]) -> Result<Vec<TaskEntry>, String> {
+    ensure_scanned(state, &root);
+    let items: Vec<TodoItem> = {
+        let projects = state.projects.lock().unwrap();
+        let project = projects.get(&root).ok_or_else(|| "Project is not scanned".to_string())?;
+        project.files.values().flatten().filter(|i| ids.contains(&i.id)).cloned().collect()
+    };
+
+    let mut imported = load_imported(&root);
+    let mut tasks = Vec::new();
+    for item in items {
+        if let Some(existing) = imported.iter().find(|i| i.todo_id == item.id) {
+            tasks.push(existing.task.clone());
+            continue;
+        }
+        let priority = match item.marker.as_str() {
+            "FIXME" => "high",
+            "HACK" | "XXX" => "medium",
+            _ => "low",
+        };
+        let title = if item.text.is_empty() {
+            format!("{} in {}", item.marker, item.path)
+        } else {
+            item.text.clone()
+        };
+        let task = TaskEntry {
+            id: Uuid::new_v4().to_string(),
+            title,
+            content: format!("{} at {}:{}", item.marker, item.path, item.line),
+            status: "pending".to_string(),
+            priority: priority.to_string(),
+            tags: vec!["todo".to_string(), item.marker.to_lowercase()],
+            timestamp: now_secs(),
+            project_path: Some(root.clone()),
+            source_file: Some(item.path.clone()),
+            source_line: Some(item.line),
+        };
+        imported.push(ImportedTodo {
+            todo_id: item.id.clone(),
+            task: task.clone(),
+        });
+        tasks.push(task);
+    }
+    save_imported(&root, &imported)?;
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_tagged_and_leading_markers() {
+        assert_eq!(match_marker("// TODO: fix this", "TODO"), Some("fix this"));
+        assert_eq!(match_marker("    # FIXME(alice): handle unicode", "FIXME"), Some("handle unicode"));
+        assert_eq!(match_marker("/* TODO tidy up */", "TODO"), Some("tidy up"));
+        assert_eq!(match_marker("<!-- TODO: html -->", "TODO"), Some("html"));
+        assert_eq!(match_marker("x = 1  # trailing TODO: later", "TODO"), Some("later"));
+        assert_eq!(match_marker("-- TODO", "TODO"), Some(""));
+    }
+
+    #[test]
+    fn skips_prose_and_partial_words() {
+        assert_eq!(match_marker("// mentions the TODO marker in prose", "TODO"), None);
+        assert_eq!(match_marker("// see the `TODO:` convention", "TODO"), None);
+        assert_eq!(match_marker("// TODOS: plural is another word", "TODO"), None);
+        assert_eq!(match_marker("let todo_list = MY_TODO;", "TODO"), None);
+    }
+
+    #[test]
+    fn ignores_markers_outside_comments() {
+        assert_eq!(match_marker("let s = \"TODO: not a comment\";", "TODO"), None);
+    }
+
+    #[test]
+    fn keeps_looking_after_a_rejected_occurrence() {
+        assert_eq!(match_marker("// XTODO then TODO: real", "TODO"), Some("real"));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::git::is_gitignored;
+use crate::local_history::record_versions;
+use crate::problems::schedule_auto_run;
+use crate::symbols::update_paths;
+use crate::todos;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Directories whose changes are never interesting to the editor or to local history
+const IGNORED_DIRS: &[&str] = &[
+    ".git",
+    ".milhouse",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".next",
+    "__pycache__",
+    ".venv",
+];
+
+/// Changes are collected for this long and then snapshotted into local history together
+const HISTORY_BATCH_DELAY: Duration = Duration::from_secs(2);
+
+/// Active watchers, keyed by canonical project path
+#[derive(Default)]
+pub struct WatcherState {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    /// Files waiting for a local history snapshot, per project; a project is listed while
+    /// its batch is scheduled
+    pending_history: Mutex<HashMap<String, HashSet<PathBuf>>>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileChange {
+    pub project_path: String,
+    /// "create", "modify" or "remove"
+    pub kind: String,
+    pub paths: Vec<String>,
+}
+
+/// Whether `path` lies in one of the ignored directories below `root`
+pub fn is_ignored_path(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|c| IGNORED_DIRS.iter().any(|d| c.as_os_str() == *d))
+}
+
+fn handle_event(app: &AppHandle, root: &str, event: Event) {
+    let kind = match event.kind {
+        EventKind::Create(_) => "create",
+        EventKind::Modify(ModifyKind::Metadata(_)) => return,
+        EventKind::Modify(_) => "modify",
+        EventKind::Remove(_) => "remove",
+        _ => return,
+    };
+
+    let paths: Vec<PathBuf> = event
+        .paths
+        .into_iter()
+        .filter(|p| !is_ignored_path(Path::new(root), p))
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+
+    if kind != "remove" {
+        schedule_history(app, root, &paths);
+    }
+
+    update_paths(app, root, &paths);
+    todos::update_paths(app, root, &paths);
+    schedule_auto_run(app, root);
+
+    let _ = app.emit(
+        "file-changed",
+        FileChange {
+            project_path: root.to_string(),
+            kind: kind.to_string(),
+            paths: paths.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        },
+    );
+}
+
+/// Queue changed files for local history, leaving out gitignored ones. The first change in a
+/// batch schedules one snapshot of everything that changed by the end of `HISTORY_BATCH_DELAY`.
+fn schedule_history(app: &AppHandle, root: &str, paths: &[PathBuf]) {
+    let root_path = Path::new(root);
+    let tracked: Vec<PathBuf> = paths
+        .iter()
+        .filter(|p| !is_gitignored(root_path, p))
+        .cloned()
+        .collect();
+    if tracked.is_empty() {
+        return;
+    }
+
+    let state = app.state::<WatcherState>();
+    let mut pending = state.pending_history.lock().unwrap();
+    let scheduled = pending.contains_key(root);
+    pending.entry(root.to_string()).or_default().extend(tracked);
+    if scheduled {
+        return;
+    }
+
+    let app = app.clone();
+    let root = root.to_string();
+    thread::spawn(move || {
+        thread::sleep(HISTORY_BATCH_DELAY);
+        let paths: Vec<PathBuf> = app
+            .state::<WatcherState>()
+            .pending_history
+            .lock()
+            .unwrap()
+            .remove(&root)
+            .map(|paths| paths.into_iter().collect())
+            .unwrap_or_default();
+        if let Err(e) = record_versions(&root, &paths, "watcher") {
+            println!("[DEBUG] Failed to record history for {}: {}", root, e);
+        }
+    });
+}
+
+/// Start watching a project recursively, emitting `file-changed` events
+#[tauri::command]
+pub fn watch_project(app: AppHandle, state: State<'_, WatcherState>, project_path: String) -> Result<String, String> {
+    let root = Path::new(&project_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve project path: {}", e))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    if watchers.contains_key(&root) {
+        return Ok(root);
+    }
+
+    let root_for_events = root.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+        Ok(event) => handle_event(&app, &root_for_events, event),
+        Err(e) => println!("[DEBUG] File watcher error: {}", e),
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    watcher
+        .watch(Path::new(&root), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch project: {}", e))?;
+
+    println!("[DEBUG] Watching project: {}", root);
+    watchers.insert(root.clone(), watcher);
+    Ok(root)
+}
+
+#[tauri::command]
+pub fn unwatch_project(state: State<'_, WatcherState>, project_path: String) -> Result<(), String> {
+    let root = Path::new(&project_path)
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(project_path);
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    // Dropping the watcher stops it
+    watchers.remove(&root);
+    Ok(())
+}
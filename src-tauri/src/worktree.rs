@@ -0,0 +1,322 @@
+use crate::checkpoint::create_checkpoint_internal;
+use crate::git::{repo_root, run_git};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BranchInfo {
+    pub name: String,
+    pub is_current: bool,
+    pub is_remote: bool,
+    pub commit: String,
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// Upstream tracking summary as reported by git, e.g. "ahead 2, behind 1"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking: Option<String>,
+    /// Worktree the branch is checked out in, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_path: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorktreeInfo {
+    pub path: String,
+    pub head: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub is_main: bool,
+    pub detached: bool,
+    pub locked: bool,
+    pub prunable: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SwitchResult {
+    pub switched: bool,
+    /// Uncommitted files that the switch would overwrite
+    pub conflicts: Vec<String>,
+    /// All uncommitted files in the working tree at the time of the switch
+    pub dirty_files: Vec<String>,
+    /// Checkpoint taken before a forced switch discarded local changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint_id: Option<String>,
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+/// Paths with uncommitted changes, including untracked files
+fn dirty_files(root: &str) -> Result<Vec<String>, String> {
+    let status = run_git(root, &["status", "--porcelain", "-z", "--untracked-files=all"])?;
+    let mut files = Vec::new();
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        files.push(entry[3..].to_string());
+        // Renames and copies are followed by their original path
+        if matches!(&entry[..1], "R" | "C") {
+            entries.next();
+        }
+    }
+    Ok(files)
+}
+
+#[tauri::command]
+pub fn list_branches(project_path: String) -> Result<Vec<BranchInfo>, String> {
+    let output = run_git(
+        &project_path,
+        &[
+            "for-each-ref",
+            "--format=%(refname)%00%(refname:short)%00%(HEAD)%00%(objectname:short)%00%(upstream:short)%00%(upstream:track,nobracket)%00%(worktreepath)%00%(contents:subject)",
+            "refs/heads",
+            "refs/remotes",
+        ],
+    )?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let refname = fields.next()?;
+            let name = fields.next()?.to_string();
+            // Skip symbolic refs like origin/HEAD
+            if refname.starts_with("refs/remotes/") && refname.ends_with("/HEAD") {
+                return None;
+            }
+            Some(BranchInfo {
+                is_current: fields.next()? == "*",
+                is_remote: refname.starts_with("refs/remotes/"),
+                commit: fields.next()?.to_string(),
+                upstream: non_empty(fields.next()?),
+                tracking: non_empty(fields.next()?),
+                worktree_path: non_empty(fields.next()?),
+                subject: fields.next().unwrap_or("").to_string(),
+                name,
+            })
+        })
+        .collect())
+}
+
+/// Reject names git would not accept for a branch, including ones that would read as options
+fn validate_branch_name(repo: &str, name: &str) -> Result<(), String> {
+    run_git(repo, &["check-ref-format", "--branch", name])
+        .map(|_| ())
+        .map_err(|_| format!("Invalid branch name: {}", name))
+}
+
+#[tauri::command]
+pub fn create_branch(
+    project_path: String,
+    name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+) -> Result<(), String> {
+    validate_branch_name(&project_path, &name)?;
+    let start = start_point.unwrap_or_else(|| "HEAD".to_string());
+    if checkout.unwrap_or(false) {
+        run_git(&project_path, &["switch", "-c", &name, "--", &start])?;
+    } else {
+        run_git(&project_path, &["branch", "--", &name, &start])?;
+    }
+    Ok(())
+}
+
+/// Switch to `name`. Unless `force` is set, nothing happens when uncommitted changes
+/// would be overwritten; the conflicting files are reported instead. A forced switch
+/// discards local changes after saving them in a checkpoint.
+#[tauri::command]
+pub fn switch_branch(
+    project_path: String,
+    name: String,
+    force: Option<bool>,
+) -> Result<SwitchResult, String> {
+    let root = repo_root(&project_path)?;
+    validate_branch_name(&root, &name)?;
+    let dirty = dirty_files(&root)?;
+
+    let conflicts: Vec<String> = if dirty.is_empty() {
+        vec![]
+    } else {
+        let target = run_git(&root, &["rev-parse", "--verify", &format!("{}^{{commit}}", name)])?;
+        let changed = run_git(&root, &["diff", "--name-only", "-z", "HEAD", target.trim()])?;
+        let changed: HashSet<&str> = changed.split('\0').filter(|p| !p.is_empty()).collect();
+        dirty
+            .iter()
+            .filter(|f| changed.contains(f.as_str()))
+            .cloned()
+            .collect()
+    };
+
+    if !conflicts.is_empty() && !force.unwrap_or(false) {
+        return Ok(SwitchResult {
+            switched: false,
+            conflicts,
+            dirty_files: dirty,
+            checkpoint_id: None,
+        });
+    }
+
+    let mut checkpoint_id = None;
+    if conflicts.is_empty() {
+        run_git(&root, &["switch", "--", &name])?;
+    } else {
+        let label = format!("Before switching to {}", name);
+        checkpoint_id = create_checkpoint_internal(&root, &label, false)?.map(|c| c.id);
+        run_git(&root, &["switch", "--discard-changes", "--", &name])?;
+    }
+
+    Ok(SwitchResult {
+        switched: true,
+        conflicts,
+        dirty_files: dirty,
+        checkpoint_id,
+    })
+}
+
+#[tauri::command]
+pub fn delete_branch(project_path: String, name: String, force: Option<bool>) -> Result<(), String> {
+    validate_branch_name(&project_path, &name)?;
+    let flag = if force.unwrap_or(false) { "-D" } else { "-d" };
+    run_git(&project_path, &["branch", flag, "--", &name])?;
+    Ok(())
+}
+
+pub fn list_worktrees_internal(project_path: &str) -> Result<Vec<WorktreeInfo>, String> {
+    let output = run_git(project_path, &["worktree", "list", "--porcelain"])?;
+
+    let mut worktrees = Vec::new();
+    // Records are separated by blank lines; the first record is the main worktree
+    for record in output.split("\n\n").filter(|r| !r.trim().is_empty()) {
+        let mut info = WorktreeInfo {
+            path: String::new(),
+            head: String::new(),
+            branch: None,
+            is_main: worktrees.is_empty(),
+            detached: false,
+            locked: false,
+            prunable: false,
+        };
+        for line in record.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "worktree" => info.path = value.to_string(),
+                "HEAD" => info.head = value.to_string(),
+                "branch" => {
+                    info.branch = Some(value.strip_prefix("refs/heads/").unwrap_or(value).to_string())
+                }
+                "detached" => info.detached = true,
+                "locked" => info.locked = true,
+                "prunable" => info.prunable = true,
+                _ => {}
+            }
+        }
+        worktrees.push(info);
+    }
+
+    Ok(worktrees)
+}
+
+/// Default location for new worktrees: a sibling `<repo>-worktrees` directory, so
+/// worktrees never end up inside the project tree itself
+fn default_worktree_path(root: &str, branch: &str) -> PathBuf {
+    let root = Path::new(root);
+    let repo_name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "repo".to_string());
+    let dir_name: String = branch
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    root.parent()
+        .unwrap_or(root)
+        .join(format!("{}-worktrees", repo_name))
+        .join(dir_name)
+}
+
+pub fn create_worktree_internal(
+    project_path: &str,
+    branch: &str,
+    new_branch: bool,
+    start_point: Option<&str>,
+    path: Option<&str>,
+) -> Result<WorktreeInfo, String> {
+    let root = repo_root(project_path)?;
+    validate_branch_name(&root, branch)?;
+    let path = path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_worktree_path(&root, branch));
+    let path_str = path.to_string_lossy().to_string();
+
+    let mut args = vec!["worktree", "add"];
+    if new_branch {
+        args.push("-b");
+        args.push(branch);
+        args.push("--");
+        args.push(&path_str);
+        args.push(start_point.unwrap_or("HEAD"));
+    } else {
+        args.push("--");
+        args.push(&path_str);
+        args.push(branch);
+    }
+    run_git(&root, &args)?;
+
+    println!("[DEBUG] Created worktree for {} at {}", branch, path_str);
+
+    let canonical = path.canonicalize().unwrap_or(path);
+    list_worktrees_internal(&root)?
+        .into_iter()
+        .find(|w| Path::new(&w.path).canonicalize().ok().as_ref() == Some(&canonical))
+        .ok_or_else(|| format!("Worktree was not created: {}", path_str))
+}
+
+pub fn remove_worktree_internal(project_path: &str, path: &str, force: bool) -> Result<(), String> {
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    args.push("--");
+    args.push(path);
+    run_git(project_path, &args)?;
+    run_git(project_path, &["worktree", "prune"])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_worktrees(project_path: String) -> Result<Vec<WorktreeInfo>, String> {
+    list_worktrees_internal(&project_path)
+}
+
+/// Create a worktree checking out `branch`, creating the branch first when `new_branch` is set
+#[tauri::command]
+pub fn create_worktree(
+    project_path: String,
+    branch: String,
+    new_branch: Option<bool>,
+    start_point: Option<String>,
+    path: Option<String>,
+) -> Result<WorktreeInfo, String> {
+    create_worktree_internal(
+        &project_path,
+        &branch,
+        new_branch.unwrap_or(false),
+        start_point.as_deref(),
+        path.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub fn remove_worktree(project_path: String, path: String, force: Option<bool>) -> Result<(), String> {
+    remove_worktree_internal(&project_path, &path, force.unwrap_or(false))
+}
@@ -1,6 +1,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import FileTree from './components/FileTree';
 import TerminalPanel from './components/Terminal';
+import TerminalProfiles from './components/TerminalProfiles';
 import Drawer, { TaskEntry, DocumentEntry } from './components/Drawer';
 import { useProject } from './hooks/useProject';
 import { useClaude } from './hooks/useClaude';
@@ -15,6 +16,8 @@ function App() {
   const [mcpMenuOpen, setMcpMenuOpen] = useState(false);
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [ralphEnabled, setRalphEnabled] = useState(false);
+  const [persistentSessions, setPersistentSessions] = useState(false);
+  const [recordClaude, setRecordClaude] = useState(false);
 
   // Initialize Claude settings when project changes (always run to ensure MCP is configured)
   useEffect(() => {
@@ -23,7 +26,7 @@ function App() {
     }
   }, [projectPath, initializeProject]);
 
-  // Update autoStartClaude and ralphEnabled from project settings
+  // Update autoStartClaude, ralphEnabled, persistentSessions and recordClaude from project settings
   useEffect(() => {
     if (projectSettings?.auto_start_claude !== undefined) {
       setAutoStartClaude(projectSettings.auto_start_claude);
@@ -31,6 +34,12 @@ function App() {
     if (projectSettings?.ralph_wiggum_enabled !== undefined) {
       setRalphEnabled(projectSettings.ralph_wiggum_enabled);
     }
+    if (projectSettings?.persistent_sessions !== undefined) {
+      setPersistentSessions(projectSettings.persistent_sessions);
+    }
+    if (projectSettings?.record_claude_sessions !== undefined) {
+      setRecordClaude(projectSettings.record_claude_sessions);
+    }
   }, [projectSettings]);
 
   // Debug logging
@@ -109,12 +118,16 @@ function App() {
     status: 'pending' | 'in_progress' | 'completed'
   ): Promise<void> => {
     try {
-      await invoke('update_task_status', { taskId, status });
+      await invoke('update_task_status', {
+        taskId,
+        status,
+        projectPath: projectPath || undefined,
+      });
     } catch (error) {
       console.error('Failed to update task status:', error);
       throw error;
     }
-  }, []);
+  }, [projectPath]);
 
   return (
     <div
@@ -241,6 +254,60 @@ function App() {
               )}
             </div>
 
+            <div className="settings-section">
+              <h4>Terminal Sessions</h4>
+              <div className="settings-row">
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={persistentSessions}
+                    onChange={(e) => {
+                      setPersistentSessions(e.target.checked);
+                      if (projectPath && projectSettings) {
+                        updateSettings(projectPath, {
+                          ...projectSettings,
+                          persistent_sessions: e.target.checked,
+                        });
+                      }
+                    }}
+                  />
+                  Keep terminals running after Milhouse closes
+                </label>
+              </div>
+              <p className="settings-desc">
+                New terminals run in a background session daemon. When Milhouse reopens the project, surviving sessions are reattached with their scrollback.
+              </p>
+              <div className="settings-row">
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={recordClaude}
+                    onChange={(e) => {
+                      setRecordClaude(e.target.checked);
+                      if (projectPath && projectSettings) {
+                        updateSettings(projectPath, {
+                          ...projectSettings,
+                          record_claude_sessions: e.target.checked,
+                        });
+                      }
+                    }}
+                  />
+                  Record Claude terminals
+                </label>
+              </div>
+              <p className="settings-desc">
+                Saves Claude terminal output as asciicast files in .milhouse/recordings, so unattended runs can be replayed later.
+              </p>
+            </div>
+
+            <div className="settings-section">
+              <h4>Terminal Profiles</h4>
+              <TerminalProfiles />
+              <p className="settings-desc">
+                Named shells for new terminals, picked next to the + button. Leave the command empty to use your login shell; environment lines starting with - unset a variable.
+              </p>
+            </div>
+
             <div className="settings-section">
               <h4>Milhouse Memory</h4>
               <div className="settings-info">
@@ -290,6 +357,8 @@ function App() {
             projectPath={projectPath}
             autoStartClaude={isClaudeInstalled && autoStartClaude}
             claudeStartCommand={getClaudeStartCommand(ralphEnabled)}
+            persistentSessions={persistentSessions}
+            recordClaude={recordClaude}
             isLoading={isLoading || claudeLoading || !claudeInitialized}
           />
         </div>
@@ -10,13 +10,72 @@ interface TerminalTab {
   id: string;
   title: string;
   initialized: boolean;
+  exit?: TerminalExit;
+  // Reported by the shell's integration script, when it has one
+  cwd?: string;
+  lastCommand?: TerminalCommand;
+}
+
+interface TerminalExit {
+  id: string;
+  exit_code: number | null;
+  signal: string | null;
+  duration_ms: number;
+  last_lines: string[];
+}
+
+interface TerminalCommand {
+  id: string;
+  command: string | null;
+  cwd: string | null;
+  started_at: number;
+  exit_code: number | null;
+  // Null while the command is still running
+  duration_ms: number | null;
 }
 
 interface TerminalOutput {
   id: string;
   data: string;
+  offset: number;
+}
+
+interface TerminalSnapshot {
+  id: string;
+  data: string;
+  offset: number;
+}
+
+interface TerminalProfile {
+  name: string;
 }
 
+interface SessionInfo {
+  id: string;
+  cwd: string | null;
+  startup_command: string | null;
+  pid: number | null;
+  created_at: number;
+}
+
+const formatDuration = (ms: number) => {
+  const seconds = Math.round(ms / 1000);
+  if (seconds < 60) return `${seconds}s`;
+  const minutes = Math.round(seconds / 60);
+  if (minutes < 60) return `${minutes}m`;
+  return `${Math.floor(minutes / 60)}h ${minutes % 60}m`;
+};
+
+// e.g. "exited 1 after 3m"
+const describeExit = (exit: TerminalExit) => {
+  const status = exit.signal
+    ? `killed by ${exit.signal}`
+    : exit.exit_code !== null
+      ? `exited ${exit.exit_code}`
+      : 'exited';
+  return `${status} after ${formatDuration(exit.duration_ms)}`;
+};
+
 // Tab component with rename support
 const TerminalTabItem = memo(function TerminalTabItem({
   tab,
@@ -81,7 +140,23 @@ const TerminalTabItem = memo(function TerminalTabItem({
           onClick={(e) => e.stopPropagation()}
         />
       ) : (
-        <span className="tab-title">{tab.title}</span>
+        <span className="tab-title" title={tab.cwd}>{tab.title}</span>
+      )}
+      {!tab.exit && tab.lastCommand && tab.lastCommand.exit_code !== null && tab.lastCommand.exit_code !== 0 && (
+        <span
+          className="tab-command-failed"
+          title={`${tab.lastCommand.command ?? 'Command'} exited ${tab.lastCommand.exit_code}`}
+        >
+          ●
+        </span>
+      )}
+      {tab.exit && (
+        <span
+          className={`tab-exit ${tab.exit.exit_code === 0 ? '' : 'failed'}`}
+          title={tab.exit.last_lines.join('\n')}
+        >
+          {describeExit(tab.exit)}
+        </span>
       )}
       <button
         className="tab-close"
@@ -101,11 +176,17 @@ interface TerminalPanelProps {
   autoStartClaude?: boolean;
   claudeStartCommand?: string;
   isLoading?: boolean;
+  // Run terminals in the session daemon so they survive the app closing
+  persistentSessions?: boolean;
+  // Record Claude terminals to .milhouse/recordings
+  recordClaude?: boolean;
 }
 
-export default function TerminalPanel({ projectPath, autoStartClaude = false, claudeStartCommand = 'claude', isLoading = false }: TerminalPanelProps) {
+export default function TerminalPanel({ projectPath, autoStartClaude = false, claudeStartCommand = 'claude', isLoading = false, persistentSessions = false, recordClaude = false }: TerminalPanelProps) {
   const [tabs, setTabs] = useState<TerminalTab[]>([]);
   const [activeTab, setActiveTab] = useState<string | null>(null);
+  const [profiles, setProfiles] = useState<TerminalProfile[]>([]);
+  const [selectedProfile, setSelectedProfile] = useState('');
   const terminalRefs = useRef<Map<string, { xterm: XTerm; fitAddon: FitAddon }>>(new Map());
   const containerRef = useRef<HTMLDivElement>(null);
   const resizeTimeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null);
@@ -117,6 +198,9 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
   const lastOutputTime = useRef<Map<string, number>>(new Map());
   const terminalCreationTime = useRef<Map<string, number>>(new Map());
   const pendingResize = useRef<Map<string, { cols: number; rows: number }>>(new Map());
+  // Scrollback offset replayed into each xterm; live output up to it is already shown
+  const attachedOffset = useRef<Map<string, number>>(new Map());
+  const queuedOutput = useRef<Map<string, TerminalOutput[]>>(new Map());
   const resizeCooldown = 500; // Minimum ms between resize operations
   const quietPeriod = 500; // Wait this long after last output before syncing PTY
 
@@ -149,11 +233,15 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
     if (projectPath !== lastProjectPath.current) {
       if (lastProjectPath.current !== null) {
         console.log('[Terminal] Project changed, resetting terminals');
-        // Close all existing terminals
+        // Close all existing terminals; persistent sessions are only detached
         terminalRefs.current.forEach((terminal, id) => {
-          invoke('kill_terminal', { id }).catch(console.error);
+          if (!persistentSessions) {
+            invoke('kill_terminal', { id }).catch(console.error);
+          }
           terminal.xterm.dispose();
         });
+        attachedOffset.current.clear();
+        queuedOutput.current.clear();
         terminalRefs.current.clear();
         setTabs([]);
         setActiveTab(null);
@@ -162,7 +250,7 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
       }
       lastProjectPath.current = projectPath;
     }
-  }, [projectPath]);
+  }, [projectPath, persistentSessions]);
 
   // Process any pending resize when output stops
   const processPendingResize = useCallback((id: string) => {
@@ -245,25 +333,51 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
   useEffect(() => {
     // Listen for terminal output - track timing and write to xterm
     const unlisten = listen<TerminalOutput>('terminal-output', (event) => {
-      const { id, data } = event.payload;
+      const { id, data, offset } = event.payload;
       const terminal = terminalRefs.current.get(id);
       if (terminal && data) {
         // Track last output time for quiet period detection
         lastOutputTime.current.set(id, Date.now());
 
+        // Hold output until the scrollback replay has been written
+        const attached = attachedOffset.current.get(id);
+        if (attached === undefined) {
+          const queue = queuedOutput.current.get(id) || [];
+          queue.push(event.payload);
+          queuedOutput.current.set(id, queue);
+          return;
+        }
+        if (offset <= attached) return;
+
         terminal.xterm.write(data);
       }
     });
 
-    // Listen for terminal exit
-    const unlistenExit = listen<string>('terminal-exit', (event) => {
-      const id = event.payload;
-      handleCloseTab(id);
+    // Listen for terminal exit - keep the tab open with its exit status until closed
+    const unlistenExit = listen<TerminalExit>('terminal-exit', (event) => {
+      const exit = event.payload;
+      setTabs((prev) => prev.map((t) => (t.id === exit.id ? { ...t, exit } : t)));
+      const terminal = terminalRefs.current.get(exit.id);
+      terminal?.xterm.write(`\r\n\x1b[2m[Process ${describeExit(exit)}]\x1b[0m\r\n`);
+    });
+
+    // Track finished commands and the shell's directory for shells with integration
+    const unlistenCommand = listen<TerminalCommand>('terminal-command', (event) => {
+      const command = event.payload;
+      if (command.duration_ms === null) return;
+      setTabs((prev) => prev.map((t) => (t.id === command.id ? { ...t, lastCommand: command } : t)));
+    });
+
+    const unlistenCwd = listen<{ id: string; cwd: string }>('terminal-cwd', (event) => {
+      const { id, cwd } = event.payload;
+      setTabs((prev) => prev.map((t) => (t.id === id ? { ...t, cwd } : t)));
     });
 
     return () => {
       unlisten.then((fn) => fn());
       unlistenExit.then((fn) => fn());
+      unlistenCommand.then((fn) => fn());
+      unlistenCwd.then((fn) => fn());
       if (resizeTimeoutRef.current) {
         clearTimeout(resizeTimeoutRef.current);
       }
@@ -283,10 +397,14 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
       hasCreatedInitialTerminal.current = true;
       // If we have a project and autoStartClaude is enabled, start claude with the configured command
       const startupCmd = autoStartClaude ? claudeStartCommand : undefined;
-      console.log('[Terminal] Creating terminal with:', { projectPath, autoStartClaude, claudeStartCommand, startupCmd });
-      createNewTerminal(projectPath, startupCmd);
+      if (persistentSessions) {
+        restoreSessions(projectPath, startupCmd);
+      } else {
+        console.log('[Terminal] Creating terminal with:', { projectPath, autoStartClaude, claudeStartCommand, startupCmd });
+        createNewTerminal(projectPath, startupCmd);
+      }
     }
-  }, [projectPath, autoStartClaude, claudeStartCommand, isLoading]);
+  }, [projectPath, autoStartClaude, claudeStartCommand, isLoading, persistentSessions]);
 
   // Initialize terminals after DOM renders
   useEffect(() => {
@@ -309,11 +427,34 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
     }
   }, [activeTab, debouncedFit]);
 
-  const createNewTerminal = async (cwd?: string, startupCommand?: string) => {
+  // Profiles are edited in settings, so refresh them whenever the picker is opened
+  const loadProfiles = useCallback(() => {
+    invoke<TerminalProfile[]>('list_terminal_profiles')
+      .then((list) => {
+        setProfiles(list);
+        setSelectedProfile((current) => (list.some((p) => p.name === current) ? current : ''));
+      })
+      .catch((err) => console.error('Failed to load terminal profiles:', err));
+  }, []);
+
+  useEffect(() => {
+    loadProfiles();
+  }, [loadProfiles]);
+
+  const createNewTerminal = async (cwd?: string, startupCommand?: string, profile?: string) => {
     try {
+      const record = recordClaude && startupCommand?.startsWith('claude');
       const id = await invoke<string>('create_terminal', {
         cwd: cwd || projectPath || null,
         startupCommand: startupCommand || null,
+        options: {
+          profile: profile || null,
+          // Claude runs are recorded so unattended sessions can be replayed
+          record: record ? { title: startupCommand } : null,
+        },
+        // Checkpoint the project before Claude starts so its changes can be rolled back
+        checkpoint: startupCommand?.startsWith('claude') ?? false,
+        detached: persistentSessions,
       });
       const tabNumber = tabs.length + 1;
 
@@ -321,7 +462,9 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
       const isClaudeTerminal = startupCommand?.startsWith('claude');
       const title = isClaudeTerminal
         ? (startupCommand?.includes('--dangerously-skip-permissions') ? 'Claude (Ralph)' : 'Claude Code')
-        : `Terminal ${tabNumber}`;
+        : profile
+          ? `${profile} ${tabNumber}`
+          : `Terminal ${tabNumber}`;
 
       // Track Claude terminal ID for restart functionality
       if (isClaudeTerminal) {
@@ -338,6 +481,46 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
     }
   };
 
+  // Reattach sessions that survived the last app run, or start fresh if there are none
+  const restoreSessions = async (cwd: string, startupCommand?: string) => {
+    let sessions: SessionInfo[] = [];
+    try {
+      sessions = await invoke<SessionInfo[]>('list_sessions', { projectPath: cwd });
+    } catch (err) {
+      console.error('Failed to list sessions:', err);
+    }
+    if (sessions.length === 0) {
+      createNewTerminal(cwd, startupCommand);
+      return;
+    }
+
+    console.log('[Terminal] Reattaching', sessions.length, 'surviving sessions');
+    const restored: TerminalTab[] = [];
+    for (const session of sessions) {
+      try {
+        await invoke('reattach_session', { id: session.id });
+      } catch (err) {
+        console.error('Failed to reattach session:', err);
+        continue;
+      }
+      const isClaudeTerminal = session.startup_command?.startsWith('claude');
+      if (isClaudeTerminal) {
+        claudeTerminalId.current = session.id;
+      }
+      const title = isClaudeTerminal
+        ? (session.startup_command?.includes('--dangerously-skip-permissions') ? 'Claude (Ralph)' : 'Claude Code')
+        : `Terminal ${restored.length + 1}`;
+      terminalCreationTime.current.set(session.id, Date.now());
+      restored.push({ id: session.id, title, initialized: false });
+    }
+    if (restored.length === 0) {
+      createNewTerminal(cwd, startupCommand);
+      return;
+    }
+    setTabs((prev) => [...prev, ...restored]);
+    setActiveTab(restored[0].id);
+  };
+
   const initializeTerminal = (id: string) => {
     const container = document.getElementById(`terminal-${id}`);
     if (!container) return;
@@ -418,6 +601,23 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
 
     terminalRefs.current.set(id, { xterm, fitAddon });
 
+    // Replay output the PTY produced before this xterm existed, then go live
+    const flushQueued = (offset: number) => {
+      attachedOffset.current.set(id, offset);
+      const queued = queuedOutput.current.get(id) || [];
+      queuedOutput.current.delete(id);
+      queued.filter((output) => output.offset > offset).forEach((output) => xterm.write(output.data));
+    };
+    invoke<TerminalSnapshot>('attach_terminal', { id })
+      .then((snapshot) => {
+        xterm.write(snapshot.data);
+        flushQueued(snapshot.offset);
+      })
+      .catch((err) => {
+        console.error('Failed to attach terminal:', err);
+        flushQueued(0);
+      });
+
     // Focus the terminal
     xterm.focus();
   };
@@ -439,6 +639,8 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
       terminal.xterm.dispose();
       terminalRefs.current.delete(id);
     }
+    attachedOffset.current.delete(id);
+    queuedOutput.current.delete(id);
 
     setTabs((prev) => prev.filter((t) => t.id !== id));
     setActiveTab((prev) => {
@@ -468,6 +670,9 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
     return () => window.removeEventListener('resize', handleResize);
   }, [activeTab, debouncedFit]);
 
+  // New terminals open where the active one's shell currently is
+  const activeCwd = tabs.find((t) => t.id === activeTab)?.cwd;
+
   return (
     <div className="terminal-panel" ref={containerRef}>
       <div className="terminal-tabs">
@@ -481,16 +686,34 @@ export default function TerminalPanel({ projectPath, autoStartClaude = false, cl
             onRename={(newTitle) => handleRenameTab(tab.id, newTitle)}
           />
         ))}
-        <button className="new-terminal-btn" onClick={() => createNewTerminal()}>
+        <button
+          className="new-terminal-btn"
+          onClick={() => createNewTerminal(activeCwd, undefined, selectedProfile || undefined)}
+          title={selectedProfile ? `New ${selectedProfile} terminal` : 'New terminal'}
+        >
           +
         </button>
+        {profiles.length > 0 && (
+          <select
+            className="terminal-profile-select"
+            value={selectedProfile}
+            onFocus={loadProfiles}
+            onChange={(e) => setSelectedProfile(e.target.value)}
+            title="Profile for new terminals"
+          >
+            <option value="">Default shell</option>
+            {profiles.map((p) => (
+              <option key={p.name} value={p.name}>{p.name}</option>
+            ))}
+          </select>
+        )}
       </div>
 
       <div className="terminal-content">
         {tabs.length === 0 ? (
           <div className="no-terminal">
             <p>No terminal open</p>
-            <button onClick={() => createNewTerminal()}>Create Terminal</button>
+            <button onClick={() => createNewTerminal(undefined, undefined, selectedProfile || undefined)}>Create Terminal</button>
           </div>
         ) : (
           tabs.map((tab) => (
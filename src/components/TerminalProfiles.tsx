@@ -0,0 +1,129 @@
+import { useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+// Stored shape, matching TerminalProfile in src-tauri/src/shell.rs
+interface TerminalProfile {
+  name: string;
+  program?: string;
+  args?: string[];
+  env?: Record<string, string>;
+  unsetEnv?: string[];
+  login?: boolean;
+}
+
+// Editable form of a profile: the command line and environment as plain text
+interface ProfileDraft {
+  name: string;
+  command: string;
+  env: string;
+  login: boolean;
+}
+
+const toDraft = (profile: TerminalProfile): ProfileDraft => ({
+  name: profile.name,
+  command: [profile.program, ...(profile.args || [])].filter(Boolean).join(' '),
+  env: [
+    ...Object.entries(profile.env || {}).map(([key, value]) => `${key}=${value}`),
+    ...(profile.unsetEnv || []).map((key) => `-${key}`),
+  ].join('\n'),
+  login: profile.login ?? !profile.program,
+});
+
+const fromDraft = (draft: ProfileDraft): TerminalProfile => {
+  const [program, ...args] = draft.command.trim().split(/\s+/).filter(Boolean);
+  const env: Record<string, string> = {};
+  const unsetEnv: string[] = [];
+  for (const line of draft.env.split('\n').map((l) => l.trim()).filter(Boolean)) {
+    if (line.startsWith('-')) {
+      unsetEnv.push(line.slice(1));
+      continue;
+    }
+    const eq = line.indexOf('=');
+    if (eq > 0) {
+      env[line.slice(0, eq)] = line.slice(eq + 1);
+    }
+  }
+  return { name: draft.name.trim(), program, args, env, unsetEnv, login: draft.login };
+};
+
+export default function TerminalProfiles() {
+  const [drafts, setDrafts] = useState<ProfileDraft[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [saved, setSaved] = useState(false);
+
+  useEffect(() => {
+    invoke<TerminalProfile[]>('list_terminal_profiles')
+      .then((profiles) => setDrafts(profiles.map(toDraft)))
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  const update = useCallback((index: number, changes: Partial<ProfileDraft>) => {
+    setDrafts((prev) => prev.map((d, i) => (i === index ? { ...d, ...changes } : d)));
+    setSaved(false);
+  }, []);
+
+  const addProfile = () => {
+    setDrafts((prev) => [...prev, { name: '', command: '', env: '', login: true }]);
+    setSaved(false);
+  };
+
+  const removeProfile = (index: number) => {
+    setDrafts((prev) => prev.filter((_, i) => i !== index));
+    setSaved(false);
+  };
+
+  const save = async () => {
+    try {
+      await invoke('save_terminal_profiles', { profiles: drafts.map(fromDraft) });
+      setError(null);
+      setSaved(true);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <>
+      <div className="profile-list">
+        {drafts.map((draft, index) => (
+          <div key={index} className="profile-item">
+            <input
+              type="text"
+              placeholder="Name"
+              value={draft.name}
+              onChange={(e) => update(index, { name: e.target.value })}
+            />
+            <input
+              type="text"
+              placeholder="Command (empty for your shell)"
+              value={draft.command}
+              onChange={(e) => update(index, { command: e.target.value })}
+            />
+            <textarea
+              rows={2}
+              placeholder={'KEY=value\n-UNSET_KEY'}
+              value={draft.env}
+              onChange={(e) => update(index, { env: e.target.value })}
+            />
+            <div className="settings-row">
+              <label>
+                <input
+                  type="checkbox"
+                  checked={draft.login}
+                  onChange={(e) => update(index, { login: e.target.checked })}
+                />
+                Login shell (-l)
+              </label>
+            </div>
+            <button onClick={() => removeProfile(index)}>Remove</button>
+          </div>
+        ))}
+      </div>
+      <div className="profile-actions">
+        <button onClick={addProfile}>Add profile</button>
+        <button onClick={save}>{saved ? 'Saved' : 'Save'}</button>
+      </div>
+      {error && <div className="settings-warning">{error}</div>}
+    </>
+  );
+}
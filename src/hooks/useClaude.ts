@@ -12,6 +12,8 @@ interface ClaudeProjectSettings {
   model?: string;
   auto_start_claude?: boolean;
   ralph_wiggum_enabled?: boolean;
+  persistent_sessions?: boolean;
+  record_claude_sessions?: boolean;
   append_system_prompt?: string;
 }
 
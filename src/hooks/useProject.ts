@@ -1,6 +1,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import { open } from '@tauri-apps/plugin-dialog';
 import { Store } from '@tauri-apps/plugin-store';
+import { invoke } from '@tauri-apps/api/core';
 
 const STORE_PATH = 'settings.json';
 const PROJECT_KEY = 'projectPath';
@@ -38,6 +39,15 @@ export function useProject(): UseProjectReturn {
     init();
   }, []);
 
+  // Watch the open project so changes land in local history and the file tree stays fresh
+  useEffect(() => {
+    if (!projectPath) return;
+    invoke('watch_project', { projectPath }).catch(console.error);
+    return () => {
+      invoke('unwatch_project', { projectPath }).catch(console.error);
+    };
+  }, [projectPath]);
+
   const selectProject = useCallback(async () => {
     try {
       const selected = await open({